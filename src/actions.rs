@@ -0,0 +1,452 @@
+//! The app's actions in one registry: the command palette lists them, the
+//! in-app shortcuts and the macOS menu bar trigger them, and
+//! `NumbatApp::run_action` carries them out.
+//!
+//! Shortcuts are written like `Cmd+Shift+P` — `Cmd` is the platform's
+//! command key (Ctrl on Linux and Windows), `Ctrl` always the Control key.
+//! The `[keybindings]` config section overrides the defaults per action.
+
+use egui::{Key, KeyboardShortcut, Modifiers};
+
+use crate::config::{KeybindingsConfig, ThemeChoice};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    CommandPalette,
+    ShowShortcuts,
+    CopyLastResult,
+    ClearHistory,
+    ResetSession,
+    Undo,
+    /// An undo toast's button: undo that step, if it's still the latest.
+    UndoStep(u64),
+    CopySessionScript,
+    SaveCheckpoint,
+    RestoreCheckpoint(String),
+    SetTheme(ThemeChoice),
+    ToggleSymbols,
+    TogglePlot,
+    ToggleInspector,
+    ToggleBrowser,
+    ToggleWatches,
+    ParameterSweep,
+    OpenSettings,
+    ShowMainWindow,
+    HideMainWindow,
+    ToggleQuickPanel,
+    Quit,
+    /// Quick panel: continue in the full window.
+    ContinueInMainWindow,
+    /// Quick panel: copy the result shown in the panel.
+    CopyCurrentResult,
+}
+
+/// Where an action's shortcut works.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    MainWindow,
+    QuickPanel,
+}
+
+impl Scope {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::MainWindow => "Main window",
+            Self::QuickPanel => "Quick panel",
+        }
+    }
+}
+
+impl Action {
+    /// The actions that don't depend on app state, in palette order.
+    pub fn fixed() -> Vec<Self> {
+        vec![
+            Self::CopyLastResult,
+            Self::ClearHistory,
+            Self::ResetSession,
+            Self::Undo,
+            Self::CopySessionScript,
+            Self::SaveCheckpoint,
+            Self::SetTheme(ThemeChoice::System),
+            Self::SetTheme(ThemeChoice::Dark),
+            Self::SetTheme(ThemeChoice::Light),
+            Self::ToggleSymbols,
+            Self::TogglePlot,
+            Self::ToggleInspector,
+            Self::ToggleBrowser,
+            Self::ToggleWatches,
+            Self::ParameterSweep,
+            Self::ShowShortcuts,
+            Self::OpenSettings,
+            Self::ShowMainWindow,
+            Self::HideMainWindow,
+            Self::ToggleQuickPanel,
+            Self::Quit,
+        ]
+    }
+
+    /// The actions that can have shortcuts, in cheat-sheet order.
+    pub fn bindable() -> Vec<Self> {
+        let mut actions = vec![Self::CommandPalette];
+        actions.extend(Self::fixed());
+        actions.extend([Self::ContinueInMainWindow, Self::CopyCurrentResult]);
+        actions
+    }
+
+    /// The key of the action in the `[keybindings]` config section.
+    pub fn id(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::CommandPalette => "command-palette",
+            Self::ShowShortcuts => "show-shortcuts",
+            Self::CopyLastResult => "copy-last-result",
+            Self::ClearHistory => "clear-history",
+            Self::ResetSession => "reset-session",
+            Self::Undo => "undo",
+            Self::UndoStep(_) => return None,
+            Self::CopySessionScript => "copy-session-script",
+            Self::RestoreCheckpoint(_) => return None,
+            Self::SaveCheckpoint => "save-checkpoint",
+            Self::SetTheme(ThemeChoice::System) => "theme-system",
+            Self::SetTheme(ThemeChoice::Dark) => "theme-dark",
+            Self::SetTheme(ThemeChoice::Light) => "theme-light",
+            Self::ToggleSymbols => "toggle-symbols",
+            Self::TogglePlot => "toggle-plot",
+            Self::ToggleInspector => "toggle-inspector",
+            Self::ToggleBrowser => "toggle-browser",
+            Self::ToggleWatches => "toggle-watches",
+            Self::ParameterSweep => "parameter-sweep",
+            Self::OpenSettings => "open-settings",
+            Self::ShowMainWindow => "show-main-window",
+            Self::HideMainWindow => "hide-main-window",
+            Self::ToggleQuickPanel => "toggle-quick-panel",
+            Self::Quit => "quit",
+            Self::ContinueInMainWindow => "quick-panel-open-in-window",
+            Self::CopyCurrentResult => "quick-panel-copy-result",
+        })
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            Self::CommandPalette => "Show All Commands".to_owned(),
+            Self::ShowShortcuts => "Keyboard Shortcuts".to_owned(),
+            Self::CopyLastResult => "Copy Last Result".to_owned(),
+            Self::ClearHistory => "Clear History".to_owned(),
+            Self::ResetSession => "Reset Session (forget all definitions)".to_owned(),
+            Self::Undo => "Undo Clear, Reset or Removal".to_owned(),
+            Self::UndoStep(_) => "Undo".to_owned(),
+            Self::CopySessionScript => "Copy Session as Script".to_owned(),
+            Self::SaveCheckpoint => "Save Checkpoint".to_owned(),
+            Self::RestoreCheckpoint(name) => format!("Restore Checkpoint: {name}"),
+            Self::SetTheme(theme) => format!("Theme: {}", theme.label()),
+            Self::ToggleSymbols => "Toggle Symbol Palette".to_owned(),
+            Self::TogglePlot => "Toggle Plot Panel".to_owned(),
+            Self::ToggleInspector => "Toggle Definitions Inspector".to_owned(),
+            Self::ToggleBrowser => "Toggle Documentation Browser".to_owned(),
+            Self::ToggleWatches => "Toggle Watch Panel".to_owned(),
+            Self::ParameterSweep => "Parameter Sweep…".to_owned(),
+            Self::OpenSettings => "Settings…".to_owned(),
+            Self::ShowMainWindow => "Show Main Window".to_owned(),
+            Self::HideMainWindow => "Hide Main Window".to_owned(),
+            Self::ToggleQuickPanel => "Toggle Quick Panel".to_owned(),
+            Self::Quit => "Quit Numbat".to_owned(),
+            Self::ContinueInMainWindow => "Continue in Main Window".to_owned(),
+            Self::CopyCurrentResult => "Copy Result".to_owned(),
+        }
+    }
+
+    pub fn scope(&self) -> Scope {
+        match self {
+            Self::ContinueInMainWindow | Self::CopyCurrentResult => Scope::QuickPanel,
+            _ => Scope::MainWindow,
+        }
+    }
+
+    /// Shortcuts bound when the config doesn't say otherwise.
+    pub fn default_bindings(&self) -> &'static [&'static str] {
+        match self {
+            Self::CommandPalette => &["Cmd+Shift+P"],
+            Self::ShowShortcuts => &["F1"],
+            Self::CopyLastResult => &["Cmd+Shift+C"],
+            Self::ClearHistory => &["Cmd+L"],
+            Self::OpenSettings => &["Cmd+Comma"],
+            Self::ShowMainWindow => &["Cmd+1"],
+            Self::HideMainWindow => &["Cmd+W"],
+            Self::Quit => &["Cmd+Q", "Ctrl+D"],
+            Self::ContinueInMainWindow => &["Cmd+Enter"],
+            Self::CopyCurrentResult => &["Cmd+C"],
+            _ => &[],
+        }
+    }
+}
+
+/// The shortcut strings bound to `action`: configured, or the defaults.
+pub fn binding_strings(config: &KeybindingsConfig, action: &Action) -> Vec<String> {
+    match action.id().and_then(|id| config.0.get(id)) {
+        Some(configured) => configured.clone(),
+        None => action
+            .default_bindings()
+            .iter()
+            .map(|s| (*s).to_owned())
+            .collect(),
+    }
+}
+
+/// Stores `bindings` for `action`, dropping the override when it matches
+/// the defaults so that the config only lists changes.
+pub fn set_binding_strings(config: &mut KeybindingsConfig, action: &Action, bindings: Vec<String>) {
+    let Some(id) = action.id() else {
+        return;
+    };
+    if bindings
+        .iter()
+        .map(String::as_str)
+        .eq(action.default_bindings().iter().copied())
+    {
+        config.0.remove(id);
+    } else {
+        config.0.insert(id.to_owned(), bindings);
+    }
+}
+
+/// The shortcuts bound to `action`; entries that don't parse are skipped.
+pub fn bindings(config: &KeybindingsConfig, action: &Action) -> Vec<KeyboardShortcut> {
+    binding_strings(config, action)
+        .iter()
+        .filter_map(|s| parse_shortcut(s).ok())
+        .collect()
+}
+
+/// The first shortcut of `action` as shown in tooltips and the palette.
+pub fn shortcut_label(
+    ctx: &egui::Context,
+    config: &KeybindingsConfig,
+    action: &Action,
+) -> Option<String> {
+    bindings(config, action)
+        .first()
+        .map(|shortcut| ctx.format_shortcut(shortcut))
+}
+
+/// The shortcuts to check each frame in `scope`, most modifiers first:
+/// egui matches modifiers loosely, so Cmd+L would also fire on Cmd+Shift+L.
+pub fn shortcuts(config: &KeybindingsConfig, scope: Scope) -> Vec<(KeyboardShortcut, Action)> {
+    let mut shortcuts: Vec<_> = Action::bindable()
+        .into_iter()
+        .filter(|action| action.scope() == scope)
+        .flat_map(|action| {
+            bindings(config, &action)
+                .into_iter()
+                .map(move |shortcut| (shortcut, action.clone()))
+        })
+        .collect();
+    shortcuts.sort_by_key(|(shortcut, _)| std::cmp::Reverse(modifier_count(shortcut.modifiers)));
+    shortcuts
+}
+
+/// Shortcuts bound to more than one action of the same scope. `Cmd+K` and
+/// `Ctrl+K` are the same shortcut off macOS.
+pub fn conflicts(config: &KeybindingsConfig) -> Vec<(KeyboardShortcut, Vec<Action>)> {
+    let mut conflicts: Vec<(KeyboardShortcut, Vec<Action>)> = Vec::new();
+    for scope in [Scope::MainWindow, Scope::QuickPanel] {
+        let shortcuts = shortcuts(config, scope);
+        for (i, (shortcut, action)) in shortcuts.iter().enumerate() {
+            let same = |other: &KeyboardShortcut| {
+                other.logical_key == shortcut.logical_key
+                    && pressed_modifiers(other.modifiers) == pressed_modifiers(shortcut.modifiers)
+            };
+            if conflicts.iter().any(|(s, _)| same(s)) {
+                continue;
+            }
+            let mut actions = vec![action.clone()];
+            actions.extend(
+                shortcuts[i + 1..]
+                    .iter()
+                    .filter(|(other, a)| same(other) && a != action)
+                    .map(|(_, a)| a.clone()),
+            );
+            if actions.len() > 1 {
+                conflicts.push((*shortcut, actions));
+            }
+        }
+    }
+    conflicts
+}
+
+fn modifier_count(modifiers: Modifiers) -> usize {
+    [
+        modifiers.alt,
+        modifiers.ctrl,
+        modifiers.shift,
+        modifiers.mac_cmd,
+        modifiers.command,
+    ]
+    .into_iter()
+    .filter(|&m| m)
+    .count()
+}
+
+/// Parses `Cmd+Shift+P`-style shortcuts.
+pub fn parse_shortcut(text: &str) -> Result<KeyboardShortcut, String> {
+    let invalid = || format!("Invalid shortcut \"{text}\"");
+    let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+    let key_name = parts.pop().filter(|k| !k.is_empty()).ok_or_else(invalid)?;
+    let key = Key::from_name(key_name).ok_or_else(invalid)?;
+    let mut modifiers = Modifiers::NONE;
+    for part in parts {
+        modifiers |= match part.to_ascii_lowercase().as_str() {
+            "cmd" | "command" => Modifiers::COMMAND,
+            "ctrl" | "control" => Modifiers::CTRL,
+            "alt" | "opt" | "option" => Modifiers::ALT,
+            "shift" => Modifiers::SHIFT,
+            _ => return Err(invalid()),
+        };
+    }
+    Ok(KeyboardShortcut::new(modifiers, key))
+}
+
+/// The config syntax of a shortcut, the inverse of `parse_shortcut`.
+pub fn format_shortcut(shortcut: &KeyboardShortcut) -> String {
+    let modifiers = shortcut.modifiers;
+    let mut parts = Vec::new();
+    if modifiers.command {
+        parts.push("Cmd");
+    }
+    if modifiers.ctrl {
+        parts.push("Ctrl");
+    }
+    if modifiers.alt {
+        parts.push("Alt");
+    }
+    if modifiers.shift {
+        parts.push("Shift");
+    }
+    parts.push(shortcut.logical_key.name());
+    parts.join("+")
+}
+
+/// The shortcut for a key press, as recorded in the settings. The
+/// platform's command key becomes `Cmd`.
+pub fn shortcut_from_press(modifiers: Modifiers, key: Key) -> KeyboardShortcut {
+    let mut normalized = Modifiers::NONE;
+    if modifiers.command {
+        normalized |= Modifiers::COMMAND;
+    }
+    // On Linux and Windows, Ctrl *is* the command key.
+    if modifiers.ctrl && (cfg!(target_os = "macos") || !modifiers.command) {
+        normalized |= Modifiers::CTRL;
+    }
+    if modifiers.alt {
+        normalized |= Modifiers::ALT;
+    }
+    if modifiers.shift {
+        normalized |= Modifiers::SHIFT;
+    }
+    KeyboardShortcut::new(normalized, key)
+}
+
+/// The modifiers a press of a shortcut with `modifiers` reports on this
+/// platform, where `Cmd` and `Ctrl` are the same key off macOS.
+pub fn pressed_modifiers(modifiers: Modifiers) -> Modifiers {
+    let mut pressed = modifiers;
+    if cfg!(target_os = "macos") {
+        pressed.mac_cmd = modifiers.command;
+    } else if modifiers.ctrl || modifiers.command {
+        pressed.ctrl = true;
+        pressed.command = true;
+    }
+    pressed
+}
+
+/// The clipboard event egui sends instead of a key event for this
+/// shortcut (Cmd+C and Cmd+X), if any.
+pub fn clipboard_event(shortcut: &KeyboardShortcut) -> Option<egui::Event> {
+    if pressed_modifiers(shortcut.modifiers) != pressed_modifiers(Modifiers::COMMAND) {
+        return None;
+    }
+    match shortcut.logical_key {
+        Key::C => Some(egui::Event::Copy),
+        Key::X => Some(egui::Event::Cut),
+        _ => None,
+    }
+}
+
+/// Consumes a press of `shortcut`, also when it arrived as a clipboard
+/// event.
+pub fn consume(input: &mut egui::InputState, shortcut: &KeyboardShortcut) -> bool {
+    if let Some(event) = clipboard_event(shortcut) {
+        let before = input.events.len();
+        input.events.retain(|e| *e != event);
+        if input.events.len() != before {
+            return true;
+        }
+    }
+    input.consume_shortcut(shortcut)
+}
+
+/// Whether the prompt's readline keymap uses this key already
+/// (e.g. Ctrl+W, which kills a word).
+pub fn shadowed_by_readline(shortcut: &KeyboardShortcut) -> bool {
+    crate::ui::readline::command_for(pressed_modifiers(shortcut.modifiers), shortcut.logical_key)
+        .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_shortcuts_are_unique_per_scope() {
+        assert!(conflicts(&KeybindingsConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn shift_shortcuts_come_first() {
+        let shortcuts = shortcuts(&KeybindingsConfig::default(), Scope::MainWindow);
+        let first_plain = shortcuts
+            .iter()
+            .position(|(s, _)| !s.modifiers.shift)
+            .unwrap();
+        assert!(shortcuts[first_plain..]
+            .iter()
+            .all(|(s, _)| !s.modifiers.shift));
+    }
+
+    #[test]
+    fn defaults_parse_and_round_trip() {
+        for action in Action::bindable() {
+            for text in action.default_bindings() {
+                let shortcut = parse_shortcut(text).unwrap();
+                assert_eq!(format_shortcut(&shortcut), *text);
+            }
+        }
+    }
+
+    #[test]
+    fn config_overrides_defaults() {
+        let mut config = KeybindingsConfig::default();
+        config.0.insert("quit".to_owned(), vec!["Cmd+Q".to_owned()]);
+        config
+            .0
+            .insert("clear-history".to_owned(), vec!["Cmd+Q".to_owned()]);
+        assert_eq!(bindings(&config, &Action::Quit).len(), 1);
+        let conflicts = conflicts(&config);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].1.len(), 2);
+    }
+
+    #[test]
+    fn defaults_are_not_stored() {
+        let mut config = KeybindingsConfig::default();
+        set_binding_strings(&mut config, &Action::ClearHistory, vec!["Cmd+K".to_owned()]);
+        assert_eq!(config.0.len(), 1);
+        set_binding_strings(&mut config, &Action::ClearHistory, vec!["Cmd+L".to_owned()]);
+        assert!(config.0.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(parse_shortcut("Cmd+Banana").is_err());
+        assert!(parse_shortcut("Hyper+K").is_err());
+        assert!(parse_shortcut("").is_err());
+    }
+}
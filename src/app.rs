@@ -4,11 +4,17 @@
 
 use egui::{ViewportCommand, ViewportId};
 
-use crate::config::AppConfig;
-use crate::engine::Engine;
-use crate::hotkey::QuickPanelHotkey;
-use crate::session::Session;
+use crate::actions::{self, Action};
+use crate::config::{AppConfig, FormattingConfig, Keymap, UnitsConfig};
+use crate::hotkey::{self, GlobalAction, GlobalHotkeys};
+use crate::session::{is_repl_command, Session};
 use crate::theme::{self, Palette};
+use crate::ui::browser::Browser;
+use crate::ui::command_palette::CommandPalette;
+use crate::ui::inspector::Inspector;
+use crate::ui::plot::PlotPanel;
+use crate::ui::readline::KillRing;
+use crate::ui::watch::WatchPanel;
 use crate::ui::{CompletionState, Toasts};
 
 /// The switch back to the Regular activation policy (Dock icon + menu
@@ -34,9 +40,14 @@ pub struct NumbatApp {
     pub applied_palette: Option<(Palette, f32)>,
 
     pub session: Session,
+    /// The formatting and unit options a refresh replaced, by the id of its
+    /// undo step.
+    config_undo: Vec<(u64, FormattingConfig, UnitsConfig)>,
     pub toasts: Toasts,
     pub completion: CompletionState,
     pub quick_completion: CompletionState,
+    /// Shared by both prompts, like the clipboard.
+    pub kill_ring: KillRing,
 
     pub logo: Option<egui::TextureHandle>,
 
@@ -48,11 +59,38 @@ pub struct NumbatApp {
     /// Activation/focus retries after opening the quick panel; macOS
     /// cooperative activation often ignores a background app's first request.
     pub quick_focus_nudges: u8,
+    /// Whether the quick panel shows the recent entries below its result.
+    pub quick_expanded: bool,
+    /// The recent entry selected with Down (0 = newest).
+    pub quick_selected: Option<usize>,
     #[cfg(target_os = "macos")]
     regular_policy_switch: RegularPolicySwitch,
     /// Where the quick panel should appear (computed from the monitor size).
     pub quick_position: Option<egui::Pos2>,
+    /// The monitor the quick panel opened on.
+    pub quick_monitor: Option<egui::Rect>,
+    /// Where the quick panel was dragged to since it opened.
+    pub quick_dragged_to: Option<egui::Pos2>,
     pub show_settings: bool,
+    /// Whether the symbol palette is shown above the main prompt.
+    pub show_symbols: bool,
+    /// The entry being edited in place, and its draft.
+    pub editing: Option<(usize, String)>,
+    /// Whether the plot panel is shown next to the history.
+    pub show_plot: bool,
+    pub plot: PlotPanel,
+    /// Whether the definitions inspector is shown left of the history.
+    pub show_inspector: bool,
+    pub inspector: Inspector,
+    /// Whether the documentation browser is shown right of the history.
+    pub show_browser: bool,
+    pub browser: Browser,
+    /// Whether the watch panel is shown above the history.
+    pub show_watches: bool,
+    pub watch: WatchPanel,
+    pub command_palette: Option<CommandPalette>,
+    /// The keyboard shortcut overlay (F1).
+    pub show_cheat_sheet: bool,
     quitting: bool,
     /// Consecutive frames on which the quick panel window could not be
     /// created (see the catch_unwind in `ui`).
@@ -61,8 +99,16 @@ pub struct NumbatApp {
     // Settings dialog state.
     pub settings_draft: AppConfig,
     pub settings_error: Option<String>,
-
-    pub hotkey: Option<QuickPanelHotkey>,
+    /// The action whose new shortcut is being recorded in the settings.
+    pub recording_shortcut: Option<Action>,
+    /// The global hotkey whose recorder is waiting for a chord.
+    pub recording_hotkey: Option<GlobalAction>,
+    /// Outcome of test-registering the last recorded global hotkey.
+    pub hotkey_check: Option<(GlobalAction, Result<(), String>)>,
+    /// The custom unit target being added: dimension and unit.
+    pub new_unit_target: (String, String),
+
+    pub hotkeys: Option<GlobalHotkeys>,
     pub hotkey_error: Option<String>,
 
     #[cfg(target_os = "macos")]
@@ -133,17 +179,17 @@ impl NumbatApp {
             flag
         };
 
-        let mut session = Session::new(Engine::new(config.format_options()));
+        let mut session = Session::new(config.engine());
         session.restore_history();
 
-        let (hotkey, hotkey_error) =
-            match QuickPanelHotkey::new(&config.ui.quick_panel_hotkey, cc.egui_ctx.clone()) {
-                Ok(hotkey) => (Some(hotkey), None),
-                Err(e) => {
-                    log::warn!("{e}");
-                    (None, Some(e))
-                }
-            };
+        let (hotkeys, hotkey_error) = match GlobalHotkeys::new(&config.ui, cc.egui_ctx.clone()) {
+            Ok((hotkeys, error)) => (Some(hotkeys), error),
+            Err(e) => (None, Some(e)),
+        };
+        if let Some(e) = &hotkey_error {
+            log::warn!("{e}");
+        }
+        let show_watches = !config.watch.expressions.is_empty();
 
         Self {
             settings_draft: config.clone(),
@@ -151,23 +197,45 @@ impl NumbatApp {
             palette: theme::DARK,
             applied_palette: None,
             session,
+            config_undo: Vec::new(),
             toasts: Toasts::default(),
             completion: CompletionState::default(),
             quick_completion: CompletionState::default(),
+            kill_ring: KillRing::default(),
             logo: load_logo(&cc.egui_ctx),
             main_visible: !start_hidden,
             quick_open: false,
             quick_just_opened: false,
             quick_had_focus: false,
             quick_focus_nudges: 0,
+            quick_expanded: false,
+            quick_selected: None,
             #[cfg(target_os = "macos")]
             regular_policy_switch: RegularPolicySwitch::Idle,
             quick_position: None,
+            quick_monitor: None,
+            quick_dragged_to: None,
             show_settings: false,
+            show_symbols: false,
+            editing: None,
+            show_plot: false,
+            plot: PlotPanel::default(),
+            show_inspector: false,
+            inspector: Inspector::default(),
+            show_browser: false,
+            browser: Browser::default(),
+            show_watches,
+            watch: WatchPanel::default(),
+            command_palette: None,
+            show_cheat_sheet: false,
             quitting: false,
             quick_panel_retries: 0,
             settings_error: None,
-            hotkey,
+            recording_shortcut: None,
+            recording_hotkey: None,
+            hotkey_check: None,
+            new_unit_target: Default::default(),
+            hotkeys,
             hotkey_error,
             #[cfg(target_os = "macos")]
             last_dead_key: None,
@@ -333,7 +401,13 @@ impl NumbatApp {
 
     pub(crate) fn close_quick_panel(&mut self) {
         self.quick_open = false;
+        self.remember_quick_panel_position();
         self.quick_completion.close();
+        self.quick_selected = None;
+        if self.config.ui.quick_panel_clear_on_close {
+            self.session.input.clear();
+            self.session.on_input_edited();
+        }
         // Activating the app for the panel raised the `reactivated` flag,
         // and `ui` could not consume it while the root window was occluded
         // by the panel — discard it, or the main window pops up after the
@@ -360,12 +434,29 @@ impl NumbatApp {
     /// Rebuilds the session by replaying the persisted history with a fresh
     /// engine — used when formatting options change, so all results are
     /// re-rendered with the new options. Equivalent to an app restart.
-    pub fn refresh_history(&mut self) {
-        let input = std::mem::take(&mut self.session.input);
-        let mut session = Session::new(Engine::new(self.config.format_options()));
-        session.restore_history();
-        session.input = input;
-        self.session = session;
+    /// Undoing it goes back to the `previous` options.
+    pub fn refresh_history(&mut self, previous: &AppConfig) {
+        self.editing = None;
+        let id = self.session.refresh(self.config.engine());
+        let session = &self.session;
+        self.config_undo.retain(|(id, _, _)| session.can_undo(*id));
+        self.config_undo
+            .push((id, previous.formatting.clone(), previous.units.clone()));
+    }
+
+    /// Puts back the options replaced by the refresh undone as step `id`.
+    fn restore_config(&mut self, id: u64) {
+        let Some(position) = self.config_undo.iter().position(|(step, _, _)| *step == id) else {
+            return;
+        };
+        let (_, formatting, units) = self.config_undo.remove(position);
+        self.settings_draft.formatting = formatting.clone();
+        self.settings_draft.units = units.clone();
+        self.config.formatting = formatting;
+        self.config.units = units;
+        if let Err(e) = self.config.save() {
+            log::warn!("{e}");
+        }
     }
 
     /// Drains native macOS menu events. Runs from `logic` rather than `ui`
@@ -379,10 +470,10 @@ impl NumbatApp {
         while let Some(action) = self.menu.poll() {
             use crate::platform::MenuAction;
             match action {
-                MenuAction::OpenSettings => self.open_settings(),
-                MenuAction::ShowMainWindow => self.open_main_window(ctx),
-                MenuAction::ClearHistory => self.session.clear(),
-                MenuAction::Quit => self.quit(ctx),
+                MenuAction::OpenSettings => self.run_action(ctx, Action::OpenSettings),
+                MenuAction::ShowMainWindow => self.run_action(ctx, Action::ShowMainWindow),
+                MenuAction::ClearHistory => self.run_action(ctx, Action::ClearHistory),
+                MenuAction::Quit => self.run_action(ctx, Action::Quit),
                 MenuAction::Edit(action) => self.pending_edit = Some(action),
             }
         }
@@ -433,42 +524,174 @@ impl NumbatApp {
         self.apply_edit_action(ctx);
 
         // Global (in-app) shortcuts for the main viewport.
-        let mut clear = false;
-        let mut settings = false;
-        let mut quit = false;
-        let mut hide = false;
-        let mut copy_last = false;
-        ctx.input_mut(|i| {
-            clear = i.consume_key(egui::Modifiers::COMMAND, egui::Key::L);
-            copy_last = i.consume_key(
-                egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
-                egui::Key::C,
-            );
-            // Settings/Quit come from the native menu on macOS.
-            if !cfg!(target_os = "macos") {
-                settings = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Comma);
-                quit = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Q);
-            }
-            quit |= i.consume_key(egui::Modifiers::CTRL, egui::Key::D);
-            hide = i.consume_key(egui::Modifiers::COMMAND, egui::Key::W);
+        let emacs = self.config.input.keymap == Keymap::Emacs;
+        let prompt_empty = self.session.input.is_empty();
+        let shortcuts = actions::shortcuts(&self.config.keybindings, actions::Scope::MainWindow);
+        let triggered = ctx.input_mut(|i| {
+            shortcuts
+                .into_iter()
+                .filter(|(shortcut, action)| match action {
+                    // The native menu on macOS has these built in.
+                    Action::OpenSettings | Action::Quit
+                        if cfg!(target_os = "macos")
+                            && action.default_bindings()[0]
+                                == actions::format_shortcut(shortcut) =>
+                    {
+                        false
+                    }
+                    // Ctrl+D quits like end-of-input in a terminal; with the
+                    // readline keymap only on an empty prompt (it deletes
+                    // forward).
+                    Action::Quit if emacs && actions::shadowed_by_readline(shortcut) => {
+                        prompt_empty
+                    }
+                    // With the readline keymap, the prompt's keys win
+                    // (Ctrl+W kills a word).
+                    _ => !emacs || !actions::shadowed_by_readline(shortcut),
+                })
+                .filter_map(|(shortcut, action)| actions::consume(i, &shortcut).then_some(action))
+                .collect::<Vec<_>>()
         });
 
-        if clear {
-            self.session.clear();
+        for action in triggered {
+            self.run_action(ctx, action);
         }
-        if copy_last {
-            if let Some(text) = self.session.last_result_plain().map(str::to_owned) {
-                self.copy_to_clipboard(ctx, text);
+    }
+
+    /// Carries out an action from the registry — from a shortcut, the
+    /// command palette, a button or the macOS menu bar.
+    pub fn run_action(&mut self, ctx: &egui::Context, action: Action) {
+        match action {
+            Action::CommandPalette => self.open_command_palette(),
+            Action::ShowShortcuts => self.show_cheat_sheet = !self.show_cheat_sheet,
+            Action::CopyLastResult => {
+                if let Some(text) = self.session.last_result_plain().map(str::to_owned) {
+                    self.copy_to_clipboard(ctx, text);
+                }
+            }
+            Action::ClearHistory => self.session.clear(),
+            Action::ResetSession => self.session.submit_line("reset".to_owned()),
+            Action::Undo | Action::UndoStep(_) => {
+                self.editing = None;
+                let undone = match action {
+                    Action::UndoStep(id) => self.session.undo_step(id),
+                    _ => self.session.undo(),
+                };
+                match undone {
+                    Some((id, notice)) => {
+                        self.restore_config(id);
+                        self.toasts.push(ctx, format!("Undone: {notice}"));
+                    }
+                    None => self.toasts.push(ctx, "Nothing to undo"),
+                }
+            }
+            Action::CopySessionScript => {
+                let script = self.session.export_script();
+                self.copy_to_clipboard(ctx, script);
+            }
+            Action::SaveCheckpoint => self.session.submit_line("checkpoint".to_owned()),
+            Action::RestoreCheckpoint(name) => {
+                // The entries are replaced.
+                self.editing = None;
+                self.session.submit_line(format!("restore {name}"));
             }
+            Action::SetTheme(theme) => {
+                self.config.ui.theme = theme;
+                self.settings_draft.ui.theme = theme;
+                if let Err(e) = self.config.save() {
+                    log::warn!("{e}");
+                }
+            }
+            Action::ToggleSymbols => self.show_symbols = !self.show_symbols,
+            Action::TogglePlot => self.show_plot = !self.show_plot,
+            Action::ToggleInspector => self.show_inspector = !self.show_inspector,
+            Action::ToggleBrowser => self.show_browser = !self.show_browser,
+            Action::ToggleWatches => self.show_watches = !self.show_watches,
+            Action::ParameterSweep => {
+                // Sweeps whatever is being typed, unless it's a command.
+                let input = self.session.input.trim();
+                let expression = if is_repl_command(input) { "" } else { input };
+                self.session.input = format!("sweep x from 0 to 10 in 5 steps: {expression}");
+                self.session.on_input_edited();
+                self.open_main_window(ctx);
+                ctx.memory_mut(|m| m.request_focus(self.main_input_id()));
+            }
+            Action::OpenSettings => self.open_settings(),
+            Action::ShowMainWindow => self.open_main_window(ctx),
+            Action::HideMainWindow => self.hide_main_window(ctx),
+            Action::ToggleQuickPanel => self.toggle_quick_panel(),
+            Action::Quit => self.quit(ctx),
+            Action::ContinueInMainWindow => {
+                self.open_main_window(ctx);
+                // The draft carries over, even if the panel clears on close.
+                let draft = std::mem::take(&mut self.session.input);
+                self.close_quick_panel();
+                self.session.input = draft;
+            }
+            Action::CopyCurrentResult => self.quick_copy_result(ctx),
         }
-        if settings {
-            self.open_settings();
+    }
+
+    /// Carries out a global hotkey's action.
+    fn run_global_action(&mut self, ctx: &egui::Context, action: GlobalAction) {
+        log::debug!("Global hotkey action {action:?}");
+        match action {
+            GlobalAction::ToggleQuickPanel => self.toggle_quick_panel(),
+            GlobalAction::ShowMainWindow => self.open_main_window(ctx),
+            GlobalAction::CopyLastResult => self.run_action(ctx, Action::CopyLastResult),
+            GlobalAction::EvaluateClipboard => {
+                let text = match hotkey::read_clipboard(self.config.ui.evaluate_primary_selection) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        log::warn!("{e}");
+                        self.toasts.push(ctx, e);
+                        return;
+                    }
+                };
+                // One line: the prompt is single-line.
+                let line = text.lines().map(str::trim).find(|l| !l.is_empty());
+                self.session.input.clear();
+                crate::ui::insert_at_cursor(
+                    ctx,
+                    egui::Id::new("quick_input"),
+                    &mut self.session.input,
+                    line.unwrap_or_default(),
+                );
+                if !self.quick_open {
+                    self.toggle_quick_panel();
+                }
+            }
         }
-        if hide {
-            self.hide_main_window(ctx);
+        #[cfg(target_os = "macos")]
+        if self.quick_open || action == GlobalAction::ShowMainWindow {
+            // A hidden app's windows are all ordered out and eframe
+            // could then never create the panel window.
+            crate::platform::unhide_app();
+            // Request activation while the press is fresh: macOS is far
+            // more willing to activate a background app right after the
+            // user interaction that asked for it.
+            crate::platform::activate_app();
         }
-        if quit {
-            self.quit(ctx);
+    }
+
+    /// Writes `contents` to a new file in the documents folder and reports
+    /// where (or what went wrong) in a toast.
+    pub fn export_file(
+        &mut self,
+        ctx: &egui::Context,
+        stem: &str,
+        extension: &str,
+        contents: &[u8],
+    ) {
+        let Some(path) = export_path(stem, extension) else {
+            self.toasts.push(ctx, "No documents folder to export to");
+            return;
+        };
+        match std::fs::write(&path, contents) {
+            Ok(()) => self
+                .toasts
+                .push(ctx, format!("Exported to {}", path.display())),
+            Err(e) => self.toasts.push(ctx, format!("Export failed: {e}")),
         }
     }
 
@@ -511,11 +734,12 @@ impl NumbatApp {
 }
 
 impl eframe::App for NumbatApp {
-    fn raw_input_hook(&mut self, _ctx: &egui::Context, _raw_input: &mut egui::RawInput) {
+    fn raw_input_hook(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
         #[cfg(target_os = "macos")]
-        crate::platform::fix_macos_dead_keys(_raw_input, &mut self.last_dead_key);
+        crate::platform::fix_macos_dead_keys(raw_input, &mut self.last_dead_key);
+        self.save_plot_screenshot(ctx, raw_input);
         #[cfg(debug_assertions)]
-        self.debug_save_screenshots(_raw_input);
+        self.debug_save_screenshots(raw_input);
     }
 
     fn clear_color(&self, _visuals: &egui::Visuals) -> [f32; 4] {
@@ -540,23 +764,40 @@ impl eframe::App for NumbatApp {
         #[cfg(target_os = "macos")]
         self.poll_menu(ctx);
 
-        // The global hotkey fired (possibly while every window was hidden
-        // or occluded).
-        if self.hotkey.as_ref().is_some_and(|h| h.take_pressed()) {
-            self.toggle_quick_panel();
-            log::debug!("Hotkey press consumed; quick_open={}", self.quick_open);
-            #[cfg(target_os = "macos")]
-            if self.quick_open {
-                // A hidden app's windows are all ordered out and eframe
-                // could then never create the panel window.
-                crate::platform::unhide_app();
-                // Request activation while the press is fresh: macOS is far
-                // more willing to activate a background app right after the
-                // user interaction that asked for it.
-                crate::platform::activate_app();
+        // A global hotkey fired (possibly while every window was hidden or
+        // occluded).
+        let pressed = self
+            .hotkeys
+            .as_ref()
+            .map(GlobalHotkeys::take_pressed)
+            .unwrap_or_default();
+        for action in pressed {
+            match self.recording_hotkey {
+                // The chord being recorded is one of our hotkeys, which the
+                // OS delivers as a hotkey press rather than a key event.
+                Some(recording) => {
+                    *recording.combo_mut(&mut self.settings_draft.ui) =
+                        action.combo(&self.config.ui).to_owned();
+                    self.recording_hotkey = None;
+                    self.hotkey_check = Some((recording, Ok(())));
+                }
+                None => self.run_global_action(ctx, action),
             }
         }
 
+        // Something was cleared, removed or reformatted (last frame, from the
+        // UI): offer to undo it.
+        if let Some((id, notice)) = self.session.undo_notice.take() {
+            self.toasts
+                .push_with_button(ctx, notice, "Undo", Action::UndoStep(id));
+        }
+        // Steps undone or forgotten since can't be undone from their toast.
+        let session = &self.session;
+        self.toasts.retain_buttons(|action| match action {
+            Action::UndoStep(id) => session.can_undo(*id),
+            _ => true,
+        });
+
         // The app was re-opened (Finder, Spotlight, Dock) while running
         // hidden in the background: bring the main window back. Handled
         // here rather than in `ui` because `ui` is skipped while the root
@@ -659,6 +900,8 @@ impl eframe::App for NumbatApp {
 
         if self.main_visible {
             self.main_window_ui(ui);
+            self.command_palette_ui(&ctx);
+            self.cheat_sheet_ui(&ctx);
         }
 
         // Embedded viewports (debug screenshot harness) paint into the root
@@ -666,9 +909,23 @@ impl eframe::App for NumbatApp {
         if ctx.embed_viewports() {
             self.show_child_viewports(&ctx);
         }
+
+        // `logic` shows the undo toast, in the next frame.
+        if self.session.undo_notice.is_some() {
+            ctx.request_repaint();
+        }
     }
 }
 
+/// A file named `<stem>.<extension>` in the documents folder, numbered
+/// (`<stem>-2.<extension>`, …) when taken.
+pub fn export_path(stem: &str, extension: &str) -> Option<std::path::PathBuf> {
+    let dir = dirs::document_dir().or_else(dirs::home_dir)?;
+    std::iter::once(dir.join(format!("{stem}.{extension}")))
+        .chain((2..).map(|n| dir.join(format!("{stem}-{n}.{extension}"))))
+        .find(|path| !path.exists())
+}
+
 fn load_logo(ctx: &egui::Context) -> Option<egui::TextureHandle> {
     let image = image::load_from_memory(include_bytes!("icons/icon.png"))
         .ok()?
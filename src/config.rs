@@ -5,8 +5,11 @@
 //! config (if one exists), so existing setups keep their formatting.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+use crate::engine::{Engine, PreferredUnits};
+
 #[cfg(target_os = "macos")]
 pub const DEFAULT_QUICK_PANEL_HOTKEY: &str = "Alt+Space";
 #[cfg(not(target_os = "macos"))]
@@ -77,6 +80,46 @@ impl ThemeChoice {
     }
 }
 
+/// What Enter does in the quick panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuickPanelEnter {
+    /// Evaluate and keep the panel open, like the main window.
+    Evaluate,
+    /// Evaluate, copy the result and close the panel.
+    CopyAndClose,
+}
+
+impl QuickPanelEnter {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Evaluate => "Evaluate",
+            Self::CopyAndClose => "Copy result & close",
+        }
+    }
+}
+
+/// Where the quick panel opens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuickPanelPosition {
+    /// Horizontally centered in the upper third, like Spotlight.
+    Centered,
+    UnderMouse,
+    /// Where it was last dragged to on that monitor.
+    Remembered,
+}
+
+impl QuickPanelPosition {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Centered => "Centered",
+            Self::UnderMouse => "Under mouse",
+            Self::Remembered => "Last position",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct UiConfig {
@@ -85,6 +128,37 @@ pub struct UiConfig {
     /// Global hotkey that summons the quick panel, e.g. "Alt+Space".
     #[serde(default = "default_quick_panel_hotkey")]
     pub quick_panel_hotkey: String,
+    /// Global hotkey that shows the main window; empty for none.
+    #[serde(default)]
+    pub main_window_hotkey: String,
+    /// Global hotkey that copies the last result; empty for none.
+    #[serde(default)]
+    pub copy_result_hotkey: String,
+    /// Global hotkey that evaluates the clipboard in the quick panel;
+    /// empty for none.
+    #[serde(default)]
+    pub evaluate_clipboard_hotkey: String,
+    /// Evaluate the X11 PRIMARY selection (the selected text) instead of
+    /// the clipboard.
+    #[serde(default)]
+    pub evaluate_primary_selection: bool,
+    #[serde(default = "default_quick_panel_enter")]
+    pub quick_panel_enter: QuickPanelEnter,
+    /// Keep the quick panel open when it loses focus.
+    #[serde(default)]
+    pub quick_panel_pinned: bool,
+    /// Clear the quick panel's input when it closes, instead of keeping
+    /// the draft for next time.
+    #[serde(default)]
+    pub quick_panel_clear_on_close: bool,
+    /// Width of the quick panel in points.
+    #[serde(default = "default_quick_panel_width")]
+    pub quick_panel_width: f32,
+    /// Font size of the quick panel's input; its result is a point smaller.
+    #[serde(default = "default_quick_panel_font_size")]
+    pub quick_panel_font_size: f32,
+    #[serde(default = "default_quick_panel_position")]
+    pub quick_panel_position: QuickPanelPosition,
     #[serde(default = "default_font_size")]
     pub font_size: f32,
     /// Start the app (hidden) when logging in, so the hotkey always works.
@@ -100,6 +174,22 @@ fn default_quick_panel_hotkey() -> String {
     DEFAULT_QUICK_PANEL_HOTKEY.to_owned()
 }
 
+fn default_quick_panel_enter() -> QuickPanelEnter {
+    QuickPanelEnter::Evaluate
+}
+
+fn default_quick_panel_width() -> f32 {
+    680.0
+}
+
+fn default_quick_panel_font_size() -> f32 {
+    20.0
+}
+
+fn default_quick_panel_position() -> QuickPanelPosition {
+    QuickPanelPosition::Centered
+}
+
 fn default_font_size() -> f32 {
     14.0
 }
@@ -109,12 +199,181 @@ impl Default for UiConfig {
         Self {
             theme: default_theme(),
             quick_panel_hotkey: default_quick_panel_hotkey(),
+            main_window_hotkey: String::new(),
+            copy_result_hotkey: String::new(),
+            evaluate_clipboard_hotkey: String::new(),
+            evaluate_primary_selection: false,
+            quick_panel_enter: default_quick_panel_enter(),
+            quick_panel_pinned: false,
+            quick_panel_clear_on_close: false,
+            quick_panel_width: default_quick_panel_width(),
+            quick_panel_font_size: default_quick_panel_font_size(),
+            quick_panel_position: default_quick_panel_position(),
             font_size: default_font_size(),
             launch_at_login: false,
         }
     }
 }
 
+/// Key bindings of the prompt, on top of the usual text editing keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Keymap {
+    Standard,
+    /// Readline/Emacs bindings (Ctrl+A, Ctrl+K, Alt+F, …), as in the numbat CLI.
+    Emacs,
+}
+
+impl Keymap {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::Emacs => "Emacs",
+        }
+    }
+}
+
+/// Editing behavior of the prompt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct InputConfig {
+    /// Insert the closing bracket or quote when typing an opening one.
+    #[serde(default = "default_true")]
+    pub auto_close_brackets: bool,
+    /// Replace `->`, `<=`, `>=` and `!=` with `→`, `≤`, `≥` and `≠` while typing.
+    #[serde(default)]
+    pub symbol_substitutions: bool,
+    #[serde(default = "default_keymap")]
+    pub keymap: Keymap,
+    /// After an entry was edited in place, re-run the later entries that
+    /// depend on it (reactive, like a notebook).
+    #[serde(default)]
+    pub recompute_dependents: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_keymap() -> Keymap {
+    Keymap::Standard
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            auto_close_brackets: true,
+            symbol_substitutions: false,
+            keymap: default_keymap(),
+            recompute_dependents: false,
+        }
+    }
+}
+
+/// Shortcut overrides by action id (see `actions.rs`), e.g.
+/// `clear-history = ["Cmd+K"]`. Actions not listed keep their defaults; an
+/// empty list unbinds.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeybindingsConfig(pub BTreeMap<String, Vec<String>>);
+
+/// The unit system results are shown in when the input has no `->`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnitSystem {
+    /// Whatever unit the calculation produced.
+    AsComputed,
+    Si,
+    /// Imperial / US customary.
+    Imperial,
+    /// The per-dimension targets in `[units.targets]`.
+    Custom,
+}
+
+impl UnitSystem {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::AsComputed => "As computed",
+            Self::Si => "SI",
+            Self::Imperial => "Imperial/US",
+            Self::Custom => "Custom",
+        }
+    }
+}
+
+/// Preferred units for results, e.g.
+///
+/// ```toml
+/// [units]
+/// system = "custom"
+/// targets = { energy = "kWh", pressure = "bar" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct UnitsConfig {
+    #[serde(default = "default_unit_system")]
+    pub system: UnitSystem,
+    /// Target unit by dimension name (case-insensitive), for the custom
+    /// system.
+    #[serde(default)]
+    pub targets: BTreeMap<String, String>,
+}
+
+fn default_unit_system() -> UnitSystem {
+    UnitSystem::AsComputed
+}
+
+impl Default for UnitsConfig {
+    fn default() -> Self {
+        Self {
+            system: default_unit_system(),
+            targets: BTreeMap::new(),
+        }
+    }
+}
+
+impl UnitsConfig {
+    pub fn preferred_units(&self) -> PreferredUnits {
+        match self.system {
+            UnitSystem::AsComputed => PreferredUnits::AsComputed,
+            UnitSystem::Si => PreferredUnits::Si,
+            UnitSystem::Imperial => PreferredUnits::Imperial,
+            UnitSystem::Custom => PreferredUnits::Custom(self.targets.clone()),
+        }
+    }
+}
+
+/// Expressions pinned to the watch panel, e.g.
+///
+/// ```toml
+/// [watch]
+/// expressions = ["budget - spent", 'now() -> tz("Asia/Tokyo")']
+/// refresh-seconds = 1.0
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WatchConfig {
+    #[serde(default)]
+    pub expressions: Vec<String>,
+    /// How often watches using the clock (`now()`, `today()`) are
+    /// re-evaluated; 0 only re-evaluates when definitions change.
+    #[serde(default = "default_refresh_seconds")]
+    pub refresh_seconds: f32,
+}
+
+fn default_refresh_seconds() -> f32 {
+    1.0
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            expressions: Vec::new(),
+            refresh_seconds: default_refresh_seconds(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct AppConfig {
@@ -122,6 +381,14 @@ pub struct AppConfig {
     pub formatting: FormattingConfig,
     #[serde(default)]
     pub ui: UiConfig,
+    #[serde(default)]
+    pub input: InputConfig,
+    #[serde(default)]
+    pub keybindings: KeybindingsConfig,
+    #[serde(default)]
+    pub units: UnitsConfig,
+    #[serde(default)]
+    pub watch: WatchConfig,
 }
 
 impl AppConfig {
@@ -181,6 +448,11 @@ impl AppConfig {
     pub fn format_options(&self) -> numbat::FormatOptions {
         self.formatting.format_options()
     }
+
+    /// A calculator engine set up with the formatting and unit preferences.
+    pub fn engine(&self) -> Engine {
+        Engine::new(self.format_options()).with_preferred_units(self.units.preferred_units())
+    }
 }
 
 #[cfg(test)]
@@ -210,6 +482,55 @@ mod tests {
         assert_eq!(parsed.ui.theme, ThemeChoice::System);
     }
 
+    #[test]
+    fn quick_panel_modes_parse() {
+        let parsed: AppConfig = toml::from_str(
+            r#"
+                [ui]
+                quick-panel-enter = "copy-and-close"
+                quick-panel-pinned = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(parsed.ui.quick_panel_enter, QuickPanelEnter::CopyAndClose);
+        assert!(parsed.ui.quick_panel_pinned);
+        assert!(!parsed.ui.quick_panel_clear_on_close);
+    }
+
+    #[test]
+    fn preferred_units_parse() {
+        let parsed: AppConfig = toml::from_str(
+            r#"
+                [units]
+                system = "custom"
+                targets = { energy = "kWh", pressure = "bar" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(parsed.units.system, UnitSystem::Custom);
+        assert_eq!(parsed.units.targets["energy"], "kWh");
+        assert_eq!(
+            AppConfig::default().units.preferred_units(),
+            PreferredUnits::AsComputed
+        );
+    }
+
+    #[test]
+    fn keybindings_parse() {
+        let parsed: AppConfig = toml::from_str(
+            r#"
+                [keybindings]
+                clear-history = ["Cmd+K"]
+                quit = []
+            "#,
+        )
+        .unwrap();
+        assert_eq!(parsed.keybindings.0["clear-history"], ["Cmd+K"]);
+        assert!(parsed.keybindings.0["quit"].is_empty());
+        let toml_str = toml::to_string_pretty(&parsed).unwrap();
+        assert_eq!(toml::from_str::<AppConfig>(&toml_str).unwrap(), parsed);
+    }
+
     #[test]
     fn legacy_numbat_cli_config_parses() {
         // The migration path parses the numbat CLI config with our own type;
@@ -1,13 +1,134 @@
 //! Thin wrapper around the numbat interpreter.
 
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
 
 use codespan_reporting::term::{self, termcolor::NoColor};
-use numbat::diagnostic::ErrorDiagnostic;
+use numbat::diagnostic::{Diagnostic, ErrorDiagnostic};
 use numbat::markup::{plain_text_format, Markup};
-use numbat::module_importer::{BuiltinModuleImporter, ChainedImporter, FileSystemImporter};
+use numbat::module_importer::{
+    BuiltinModuleImporter, ChainedImporter, FileSystemImporter, ModuleImporter,
+};
 use numbat::resolver::CodeSource;
-use numbat::{Context, FormatOptions, InterpreterSettings, NumbatError};
+use numbat::value::Value;
+use numbat::{
+    Context, FormatOptions, InterpreterResult, InterpreterSettings, NumbatError, Statement, Type,
+};
+
+use crate::syntax::{self, NameKind, Names};
+
+/// Units everyday conversions go to, most familiar first. Other units of a
+/// result's dimension rank after these.
+const COMMON_UNITS: &[&str] = &[
+    "m", "mi", "ft", "nmi", "in", "yd", "s", "min", "h", "day", "week", "year", "g", "lb", "oz",
+    "t", "L", "gal", "cup", "K", "°C", "°F", "J", "kWh", "cal", "eV", "W", "hp", "Pa", "bar",
+    "psi", "atm", "N", "lbf", "Hz", "rpm", "mph", "kph", "knot", "B", "bit", "deg", "rad", "ha",
+    "acre", "USD", "EUR", "GBP", "JPY", "CHF",
+];
+
+/// Operators that convert a value to a unit.
+pub const CONVERSION_OPERATORS: &[&str] = &["->", "→", "➞", " to "];
+
+/// Units of one dimension in the SI and imperial systems.
+struct SystemUnits {
+    /// As numbat names it, e.g. "Length".
+    dimension: &'static str,
+    /// Units results are converted to, smallest first.
+    si: &'static [&'static str],
+    imperial: &'static [&'static str],
+    /// Units (without metric prefixes) already in the system; results in
+    /// them are left alone.
+    metric: &'static [&'static str],
+    customary: &'static [&'static str],
+}
+
+const SYSTEM_UNITS: &[SystemUnits] = &[
+    SystemUnits {
+        dimension: "Length",
+        si: &["mm", "cm", "m", "km"],
+        imperial: &["in", "ft", "mi"],
+        metric: &["m", "Å"],
+        customary: &["in", "ft", "yd", "mi", "nmi", "thou"],
+    },
+    SystemUnits {
+        dimension: "Area",
+        si: &["cm²", "m²", "km²"],
+        imperial: &["in²", "ft²", "acre", "mi²"],
+        metric: &["m²", "ha", "are"],
+        customary: &["in²", "ft²", "yd²", "acre", "mi²"],
+    },
+    SystemUnits {
+        dimension: "Volume",
+        si: &["mL", "L", "m³"],
+        imperial: &["floz", "gal"],
+        metric: &["L", "m³"],
+        customary: &["floz", "cup", "pint", "quart", "gal", "in³", "ft³"],
+    },
+    SystemUnits {
+        dimension: "Mass",
+        si: &["g", "kg", "tonne"],
+        imperial: &["oz", "lb"],
+        metric: &["g", "tonne"],
+        customary: &["oz", "lb", "stone", "grain"],
+    },
+    SystemUnits {
+        dimension: "Velocity",
+        si: &["km/h"],
+        imperial: &["mph"],
+        metric: &["m/s", "m/h", "kph"],
+        customary: &["mph", "ft/s", "knot"],
+    },
+    SystemUnits {
+        dimension: "Force",
+        si: &["N", "kN"],
+        imperial: &["lbf"],
+        metric: &["N"],
+        customary: &["lbf"],
+    },
+    SystemUnits {
+        dimension: "Pressure",
+        si: &["Pa", "kPa", "bar"],
+        imperial: &["psi"],
+        metric: &["Pa", "bar"],
+        customary: &["PSI", "inHg"],
+    },
+    SystemUnits {
+        dimension: "Energy",
+        si: &["J", "kJ", "MJ"],
+        imperial: &["BTU"],
+        metric: &["J", "Wh", "eV", "cal"],
+        customary: &["BTU"],
+    },
+    SystemUnits {
+        dimension: "Power",
+        si: &["W", "kW", "MW"],
+        imperial: &["hp"],
+        metric: &["W"],
+        customary: &["hp"],
+    },
+];
+
+/// The units results are shown in when the input does not convert them
+/// itself.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum PreferredUnits {
+    /// Whatever unit the calculation produced.
+    #[default]
+    AsComputed,
+    Si,
+    Imperial,
+    /// Target unit by dimension name, e.g. `energy` → `kWh`.
+    Custom(BTreeMap<String, String>),
+}
+
+/// A unit a result can be converted to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitChoice {
+    /// The name offered for `-> name`: the shortest abbreviation, if any.
+    pub name: String,
+    /// All names of the unit, to recognize earlier conversions to it.
+    pub aliases: Vec<String>,
+}
 
 /// Everything produced by evaluating one line of input.
 #[derive(Default)]
@@ -18,12 +139,90 @@ pub struct EvalOutput {
     pub result: Option<Markup>,
     /// Plain-text version of the value, for the clipboard.
     pub result_plain: Option<String>,
+    /// Other units of the value's dimension, most common first.
+    pub conversions: Vec<UnitChoice>,
+    /// The value as computed (display markup and plain text), when the
+    /// result is shown in the preferred units instead.
+    pub original: Option<(Markup, String)>,
     pub error: Option<String>,
 }
 
+/// The live preview of an input line.
+#[derive(Clone)]
+pub struct PreviewValue {
+    pub markup: Markup,
+    pub plain: String,
+    pub conversions: Vec<UnitChoice>,
+}
+
+/// Series sampled over a common range, in the units of the axes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plot {
+    pub x_unit: String,
+    pub y_unit: String,
+    /// The points of each series, or why it could not be sampled.
+    pub series: Vec<Result<Vec<[f64; 2]>, String>>,
+}
+
+/// An expression evaluated for each value of a variable stepped over a
+/// range.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sweep {
+    pub variable: String,
+    pub expression: String,
+    /// Each value of the variable, with the result (or error) for it.
+    pub rows: Vec<(String, Result<String, String>)>,
+}
+
+impl Sweep {
+    /// The table as CSV, a header row first.
+    pub fn to_csv(&self) -> String {
+        let field = |text: &str| {
+            if text.contains([',', '"', '\n']) {
+                format!("\"{}\"", text.replace('"', "\"\""))
+            } else {
+                text.to_owned()
+            }
+        };
+        let mut csv = format!("{},{}\n", field(&self.variable), field(&self.expression));
+        for (value, result) in &self.rows {
+            let result = match result {
+                Ok(result) => result.clone(),
+                Err(e) => format!("error: {e}"),
+            };
+            csv.push_str(&format!("{},{}\n", field(value), field(&result)));
+        }
+        csv
+    }
+}
+
+/// The definitions of an engine at some point, to return to later.
+#[derive(Clone)]
+pub struct EngineState(Context);
+
+/// Something the context defines, for the documentation browser.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CatalogItem {
+    pub name: String,
+    pub kind: NameKind,
+    /// The module defining it (`units::si`), or `user` for the session.
+    pub module: String,
+    /// The dimension of a unit or constant, or a function's signature.
+    pub detail: String,
+    /// Other names of a unit (`m`, `meters`).
+    pub aliases: Vec<String>,
+    /// Code using it, to try it out in the prompt; just the name of a
+    /// function without a documented example.
+    pub example: String,
+}
+
+/// The module of what the session itself defined.
+pub const USER_MODULE: &str = "user";
+
 pub struct Engine {
     context: Context,
     pub format_options: FormatOptions,
+    pub preferred_units: PreferredUnits,
 }
 
 impl Engine {
@@ -31,13 +230,38 @@ impl Engine {
         Self {
             context: fresh_context(),
             format_options,
+            preferred_units: PreferredUnits::AsComputed,
         }
     }
 
+    pub fn with_preferred_units(mut self, preferred_units: PreferredUnits) -> Self {
+        self.preferred_units = preferred_units;
+        self
+    }
+
     pub fn reset(&mut self) {
         self.context = fresh_context();
     }
 
+    pub fn snapshot(&self) -> EngineState {
+        EngineState(self.context.clone())
+    }
+
+    pub fn restore(&mut self, state: &EngineState) {
+        self.context = state.0.clone();
+    }
+
+    /// Evaluates `line` for its definitions only, e.g. when rebuilding the
+    /// state of a session; output and errors are dropped.
+    pub fn replay(&mut self, line: &str) {
+        let mut settings = InterpreterSettings {
+            print_fn: Box::new(|_: &Markup| {}),
+        };
+        let _ = self
+            .context
+            .interpret_with_settings(&mut settings, line, CodeSource::Text);
+    }
+
     pub fn eval(&mut self, line: &str) -> EvalOutput {
         let printed = Arc::new(Mutex::new(Vec::new()));
         let printed_sink = Arc::clone(&printed);
@@ -59,22 +283,28 @@ impl Engine {
         match result {
             Ok((statements, interpreter_result)) => {
                 if interpreter_result.is_value() {
-                    let registry = self.context.dimension_registry();
-                    output.result = Some(interpreter_result.to_markup(
+                    let computed =
+                        self.markups(&self.context, statements.last(), &interpreter_result, true);
+                    match self.to_preferred_units(
+                        &self.context,
+                        line,
                         statements.last(),
-                        registry,
+                        &interpreter_result,
                         true,
-                        false,
-                        &self.format_options,
-                    ));
-                    let plain = interpreter_result.to_markup(
-                        statements.last(),
-                        registry,
-                        false,
-                        false,
-                        &self.format_options,
-                    );
-                    output.result_plain = Some(plain_text_format(&plain, false).trim().to_owned());
+                    ) {
+                        Some(preferred) => {
+                            output.result = Some(preferred.markup);
+                            output.result_plain = Some(preferred.plain);
+                            output.conversions = preferred.conversions;
+                            output.original = Some(computed);
+                        }
+                        None => {
+                            output.result = Some(computed.0);
+                            output.result_plain = Some(computed.1);
+                            output.conversions =
+                                conversions(&self.context, statements.last(), &interpreter_result);
+                        }
+                    }
                 }
             }
             Err(e) => output.error = Some(self.format_error(&e)),
@@ -83,10 +313,112 @@ impl Engine {
         output
     }
 
+    /// The display markup (optionally with type information) and plain
+    /// text of a value.
+    fn markups(
+        &self,
+        context: &Context,
+        statement: Option<&Statement>,
+        result: &InterpreterResult,
+        with_type_info: bool,
+    ) -> (Markup, String) {
+        let registry = context.dimension_registry();
+        let markup = result.to_markup(
+            statement,
+            registry,
+            with_type_info,
+            false,
+            &self.format_options,
+        );
+        let plain = result.to_markup(statement, registry, false, false, &self.format_options);
+        (markup, plain_text_format(&plain, false).trim().to_owned())
+    }
+
+    /// The value `context` just computed for `line`, converted to the
+    /// preferred units by evaluating `ans -> unit` on a copy. `None` when
+    /// the line converts explicitly, or the value is in a preferred unit
+    /// already.
+    fn to_preferred_units(
+        &self,
+        context: &Context,
+        line: &str,
+        statement: Option<&Statement>,
+        result: &InterpreterResult,
+        with_type_info: bool,
+    ) -> Option<PreviewValue> {
+        let InterpreterResult::Value(Value::Quantity(quantity)) = result else {
+            return None;
+        };
+        if self.preferred_units == PreferredUnits::AsComputed
+            || quantity.unit().is_scalar()
+            || CONVERSION_OPERATORS.iter().any(|op| line.contains(op))
+        {
+            return None;
+        }
+        let type_ = value_type(statement?)?;
+        let dimension =
+            plain_text_format(&type_.to_readable_type(context.dimension_registry()), false);
+        // Dimensions with several names read "Energy or Torque".
+        let is_dimension = |name: &str| {
+            dimension
+                .split(" or ")
+                .any(|part| part.trim().eq_ignore_ascii_case(name.trim()))
+        };
+        let unit = quantity.unit().to_string();
+        let unprefixed = quantity.unit().without_prefixes().to_string();
+
+        let targets: Vec<&str> = match &self.preferred_units {
+            PreferredUnits::AsComputed => return None,
+            PreferredUnits::Custom(targets) => targets
+                .iter()
+                .find(|(name, _)| is_dimension(name))
+                .map(|(_, target)| vec![target.as_str()])?,
+            PreferredUnits::Si | PreferredUnits::Imperial => {
+                let system = SYSTEM_UNITS.iter().find(|s| is_dimension(s.dimension))?;
+                let (targets, kept) = if self.preferred_units == PreferredUnits::Si {
+                    (system.si, system.metric)
+                } else {
+                    (system.imperial, system.customary)
+                };
+                if kept.contains(&unprefixed.as_str()) {
+                    return None;
+                }
+                targets.to_vec()
+            }
+        };
+        if targets.contains(&unit.as_str()) {
+            return None;
+        }
+
+        // The largest unit the value is at least 1 in: 2.6 mi rather than
+        // 13_780 ft, but 11.8 in rather than 0.98 ft.
+        let mut scratch = context.clone();
+        let mut best = None;
+        for target in targets {
+            let Ok((_, InterpreterResult::Value(Value::Quantity(value)))) =
+                scratch.interpret(&format!("ans -> {target}"), CodeSource::Internal)
+            else {
+                continue;
+            };
+            if best.is_none() || value.unsafe_value().to_f64().abs() >= 1.0 {
+                best = Some(target);
+            }
+        }
+
+        let code = format!("ans -> {}", best?);
+        let (statements, converted) = scratch.interpret(&code, CodeSource::Internal).ok()?;
+        let (markup, plain) = self.markups(&scratch, statements.last(), &converted, with_type_info);
+        Some(PreviewValue {
+            markup,
+            plain,
+            conversions: conversions(&scratch, statements.last(), &converted),
+        })
+    }
+
     /// Evaluates `line` on a throwaway copy of the context, so definitions,
     /// prints and other side effects are discarded. Used for the live
-    /// result preview while typing. Returns `(display_markup, plain_text)`.
-    pub fn preview(&self, line: &str) -> Option<(Markup, String)> {
+    /// result preview while typing.
+    pub fn preview(&self, line: &str) -> Option<PreviewValue> {
         let line = line.trim();
         if line.is_empty() {
             return None;
@@ -103,16 +435,253 @@ impl Engine {
             return None;
         }
 
-        let registry = scratch.dimension_registry();
-        let markup = result.to_markup(
-            statements.last(),
-            registry,
-            false,
-            false,
-            &self.format_options,
-        );
-        let plain = plain_text_format(&markup, false).trim().to_owned();
-        Some((markup, plain))
+        let preferred = self.to_preferred_units(&scratch, line, statements.last(), &result, false);
+        preferred.or_else(|| {
+            let (markup, plain) = self.markups(&scratch, statements.last(), &result, false);
+            Some(PreviewValue {
+                markup,
+                plain,
+                conversions: conversions(&scratch, statements.last(), &result),
+            })
+        })
+    }
+
+    /// Evaluates `expression` with `variable` set to each of `steps` values
+    /// from `from` to `to`, every time on a fresh copy of the context — like
+    /// `preview`, nothing it does sticks.
+    pub fn sweep(
+        &self,
+        variable: &str,
+        from: &str,
+        to: &str,
+        steps: usize,
+        expression: &str,
+    ) -> Result<Sweep, String> {
+        let mut settings = InterpreterSettings {
+            print_fn: Box::new(|_: &Markup| {}),
+        };
+        let mut range = self.context.clone();
+        let _ = range
+            .interpret_with_settings(
+                &mut settings,
+                &format!("let __sweep = linspace({from}, {to}, {steps})"),
+                CodeSource::Internal,
+            )
+            .map_err(|e| self.error_summary(&e))?;
+
+        let rows = (0..steps)
+            .map(|index| {
+                let mut scratch = range.clone();
+                let mut run = |code: &str| -> Result<String, String> {
+                    let (statements, result) = scratch
+                        .interpret_with_settings(&mut settings, code, CodeSource::Internal)
+                        .map_err(|e| self.error_summary(&e))?;
+                    Ok(self.markups(&scratch, statements.last(), &result, false).1)
+                };
+                run(&format!("let {variable} = element_at({index}, __sweep)"))?;
+                Ok((run(variable)?, run(expression)))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Sweep {
+            variable: variable.to_owned(),
+            expression: expression.to_owned(),
+            rows,
+        })
+    }
+
+    /// Samples each series — the name of a one-argument function, or an
+    /// expression in `x` — at `count` points from `from` to `to` (both
+    /// with units), on a copy of the context with prints dropped. The y
+    /// axis takes the unit of the first series that could be sampled; the
+    /// others are converted.
+    pub fn plot(
+        &self,
+        series: &[String],
+        from: &str,
+        to: &str,
+        count: usize,
+    ) -> Result<Plot, String> {
+        let mut settings = InterpreterSettings {
+            print_fn: Box::new(|_: &Markup| {}),
+        };
+        let mut scratch = self.context.clone();
+        let mut run = |code: &str| match scratch.interpret_with_settings(
+            &mut settings,
+            code,
+            CodeSource::Internal,
+        ) {
+            Ok((_, InterpreterResult::Value(value))) => Ok(value),
+            Ok(_) => Err("The expression has no value".to_owned()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        let Value::List(xs) = run(&format!("linspace({from}, {to}, {count})"))? else {
+            return Err("The range must consist of quantities".to_owned());
+        };
+        let xs: Vec<_> = xs.iter().collect();
+        // Numbat drops the unit of a zero, so look past it.
+        let x_unit = xs
+            .iter()
+            .filter_map(|x| match x {
+                Value::Quantity(x) => Some(x),
+                _ => None,
+            })
+            .find(|x| x.unsafe_value().to_f64() != 0.0)
+            .map(|x| x.unit().clone())
+            .ok_or("The range must consist of quantities")?;
+        let xs = xs
+            .into_iter()
+            .map(|x| match x {
+                Value::Quantity(x) if x.unsafe_value().to_f64() == 0.0 => Ok(0.0),
+                Value::Quantity(x) => Ok(x
+                    .convert_to(&x_unit)
+                    .map_err(|e| e.to_string())?
+                    .unsafe_value()
+                    .to_f64()),
+                _ => Err("The range must consist of quantities".to_owned()),
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        let _ = scratch
+            .interpret_with_settings(
+                &mut settings,
+                &format!("let __plot_x = linspace({from}, {to}, {count})"),
+                CodeSource::Internal,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let function_names: Vec<String> = scratch
+            .function_names()
+            .map(|name| name.to_string())
+            .collect();
+        let mut y_unit = None;
+        let mut sampled = Vec::new();
+        for (index, expression) in series.iter().enumerate() {
+            let expression = expression.trim();
+            let function = if function_names.iter().any(|name| name == expression) {
+                expression.to_owned()
+            } else {
+                let function = format!("__plot_f{index}");
+                let mut run = |code: &str| {
+                    scratch
+                        .interpret_with_settings(&mut settings, code, CodeSource::Internal)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                };
+                if let Err(e) = run(&format!("fn {function}(x) = {expression}")) {
+                    sampled.push(Err(e));
+                    continue;
+                }
+                function
+            };
+            let (ys, type_) = match scratch.interpret_with_settings(
+                &mut settings,
+                &format!("map({function}, __plot_x)"),
+                CodeSource::Internal,
+            ) {
+                Ok((statements, InterpreterResult::Value(Value::List(ys)))) => {
+                    let type_ = match statements.last().and_then(value_type) {
+                        Some(Type::List(type_)) => Some(*type_),
+                        _ => None,
+                    };
+                    (ys, type_)
+                }
+                Ok(_) => {
+                    sampled.push(Err("Not a function of one argument".to_owned()));
+                    continue;
+                }
+                Err(e) => {
+                    sampled.push(Err(e.to_string()));
+                    continue;
+                }
+            };
+            // Label the axis with the dimension's unit (`N` rather than
+            // `kg·km/h²`), unless the series converts to a unit itself.
+            let converts = CONVERSION_OPERATORS
+                .iter()
+                .any(|op| expression.contains(op));
+            if y_unit.is_none() && !converts {
+                if let Some(Value::Quantity(unit)) =
+                    type_.and_then(|type_| coherent_unit(&mut scratch, &mut settings, &type_))
+                {
+                    y_unit = Some(unit.unit().clone());
+                }
+            }
+            let points = ys
+                .iter()
+                .zip(&xs)
+                .map(|(y, x)| {
+                    let Value::Quantity(y) = y else {
+                        return Err("Only quantities can be plotted".to_owned());
+                    };
+                    if y.unsafe_value().to_f64() == 0.0 {
+                        return Ok([*x, 0.0]);
+                    }
+                    let unit = y_unit.get_or_insert_with(|| y.full_simplify().unit().clone());
+                    let y = y
+                        .convert_to(unit)
+                        .map_err(|_| format!("Not convertible to the y axis unit ({unit})"))?;
+                    Ok([*x, y.unsafe_value().to_f64()])
+                })
+                .collect::<Result<Vec<_>, String>>();
+            sampled.push(points);
+        }
+
+        Ok(Plot {
+            x_unit: x_unit.to_string(),
+            y_unit: y_unit.map(|unit| unit.to_string()).unwrap_or_default(),
+            series: sampled,
+        })
+    }
+
+    /// The dimension of `unit`, as numbat names it, e.g. "Energy or
+    /// Torque" for `kWh`.
+    pub fn dimension_of(&self, unit: &str) -> Result<String, String> {
+        let mut scratch = self.context.clone();
+        let code = format!("1 {unit}");
+        let (statements, _) = scratch
+            .interpret(&code, CodeSource::Internal)
+            .map_err(|_| format!("Unknown unit “{unit}”"))?;
+        let type_ = statements
+            .last()
+            .and_then(value_type)
+            .ok_or_else(|| format!("Unknown unit “{unit}”"))?;
+        let dimension = type_.to_readable_type(scratch.dimension_registry());
+        Ok(plain_text_format(&dimension, false).to_string())
+    }
+
+    /// The value of the variable `name` as plain text, and as a number
+    /// with its unit when it is a nonzero quantity (numbat drops the unit
+    /// of a zero).
+    pub fn variable(&self, name: &str) -> Option<(String, Option<(f64, String)>)> {
+        let mut scratch = self.context.clone();
+        let (statements, result) = scratch.interpret(name, CodeSource::Internal).ok()?;
+        if !result.is_value() {
+            return None;
+        }
+        let (_, plain) = self.markups(&scratch, statements.last(), &result, false);
+        let quantity = match &result {
+            InterpreterResult::Value(Value::Quantity(q)) if q.unsafe_value().to_f64() != 0.0 => {
+                Some((q.unsafe_value().to_f64(), q.unit().to_string()))
+            }
+            _ => None,
+        };
+        Some((plain, quantity))
+    }
+
+    /// The signature of the function `name`, like `fn f(x: Length) -> Area`.
+    pub fn signature(&self, name: &str) -> Option<String> {
+        self.context
+            .functions()
+            .find(|function| function.fn_name == name)
+            .map(|function| function.signature_str.to_string())
+    }
+
+    /// What the session defined or imported beyond the prelude: units,
+    /// functions, constants and dimensions, with their modules, sorted
+    /// like `builtin_catalog`, which has the rest.
+    pub fn user_catalog(&self) -> Vec<CatalogItem> {
+        catalog_items(&self.context, Some(builtins()))
     }
 
     pub fn completions(&self, word_part: &str) -> Vec<String> {
@@ -122,6 +691,11 @@ impl Engine {
         self.context.get_completions_for(word_part, true).collect()
     }
 
+    /// The names currently defined, for input highlighting.
+    pub fn names(&self) -> Names {
+        Names::from_context(&self.context)
+    }
+
     /// Markup for the `list` command.
     pub fn environment_markup(&self) -> Markup {
         self.context.print_environment()
@@ -132,10 +706,10 @@ impl Engine {
         self.context.print_info_for_keyword(keyword)
     }
 
-    /// Renders a numbat error as a plain-text compiler-style diagnostic
-    /// (with source snippet and span markers).
-    fn format_error(&self, error: &NumbatError) -> String {
-        let diagnostics = match error {
+    /// The diagnostics of a numbat error, for `error_summary` and
+    /// `format_error` to render.
+    fn diagnostics(&self, error: &NumbatError) -> Vec<Diagnostic> {
+        match error {
             NumbatError::ResolverError(e) => e.diagnostics(),
             NumbatError::NameResolutionError(e) => e.diagnostics(),
             NumbatError::TypeCheckError(e) => e.diagnostics(),
@@ -144,7 +718,33 @@ impl Engine {
                 error: e,
             }
             .diagnostics(),
+        }
+    }
+
+    /// The gist of a numbat error in one line, for table cells and the like.
+    fn error_summary(&self, error: &NumbatError) -> String {
+        let diagnostics = self.diagnostics(error);
+        // The headline is generic ("while type checking"); the labels and
+        // notes say what went wrong.
+        let first = diagnostics.first();
+        let label = first.and_then(|d| d.labels.iter().rev().find(|l| !l.message.is_empty()));
+        let message = match (label, first.and_then(|d| d.notes.first())) {
+            (Some(label), _) => label.message.clone(),
+            (None, Some(note)) => note.clone(),
+            (None, None) => error.to_string(),
         };
+        let message = message.lines().next().unwrap_or_default().trim();
+        let mut chars = message.chars();
+        chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    }
+
+    /// Renders a numbat error as a plain-text compiler-style diagnostic
+    /// (with source snippet and span markers).
+    fn format_error(&self, error: &NumbatError) -> String {
+        let diagnostics = self.diagnostics(error);
 
         let mut buffer = NoColor::new(Vec::new());
         let config = term::Config::default();
@@ -163,6 +763,284 @@ impl Engine {
     }
 }
 
+/// The units registered in `context` with the dimension of a quantity
+/// result, other than the one it is shown in.
+fn conversions(
+    context: &Context,
+    statement: Option<&Statement>,
+    result: &InterpreterResult,
+) -> Vec<UnitChoice> {
+    let InterpreterResult::Value(Value::Quantity(quantity)) = result else {
+        return Vec::new();
+    };
+    let Some(type_) = statement.and_then(value_type) else {
+        return Vec::new();
+    };
+    if quantity.unit().is_scalar() {
+        return Vec::new();
+    }
+    let current = quantity.unit().to_string();
+
+    let mut choices: Vec<(usize, usize, UnitChoice)> = context
+        .unit_representations()
+        .filter(|(_, (_, metadata))| metadata.type_ == type_)
+        .filter_map(|(name, (_, metadata))| {
+            let aliases: Vec<String> = metadata
+                .aliases
+                .iter()
+                .map(|(alias, _)| alias.to_string())
+                .collect();
+            if name == current || aliases.contains(&current) {
+                return None;
+            }
+            // Prefer lowercase abbreviations: `nmi` over `NM`, `au` over `AU`.
+            let short = metadata
+                .aliases
+                .iter()
+                .filter(|(_, prefix)| prefix.short)
+                .map(|(alias, _)| alias.as_str())
+                .min_by_key(|alias| (alias.chars().any(char::is_uppercase), alias.chars().count()));
+            let rank = COMMON_UNITS
+                .iter()
+                .position(|common| aliases.iter().any(|alias| alias == common))
+                .unwrap_or(usize::MAX);
+            let choice = UnitChoice {
+                name: short.unwrap_or(&name).to_owned(),
+                aliases,
+            };
+            Some((rank, metadata.code_source_id, choice))
+        })
+        .collect();
+    choices.sort_by(|a, b| (a.0, a.1, &a.2.name).cmp(&(b.0, b.1, &b.2.name)));
+    choices.into_iter().map(|(_, _, choice)| choice).collect()
+}
+
+/// The type of an expression statement's value.
+fn value_type(statement: &Statement) -> Option<Type> {
+    let scheme = statement.as_expression()?.get_type_scheme();
+    Some(
+        scheme
+            .instantiate_for_printing(None::<std::iter::Empty<&str>>)
+            .0
+            .inner,
+    )
+}
+
+/// A unit of dimension `type_` that is coherent with the SI base units
+/// (`1 N` for a force, not `1 lbf`), as a quantity; `None` when numbat
+/// defines no such unit, e.g. for a velocity.
+fn coherent_unit(
+    context: &mut Context,
+    settings: &mut InterpreterSettings,
+    type_: &Type,
+) -> Option<Value> {
+    let names: Vec<String> = context
+        .unit_representations()
+        .filter(|(_, (_, metadata))| metadata.type_ == *type_)
+        .map(|(name, _)| name.to_string())
+        .collect();
+    let mut units: Vec<Value> = names
+        .iter()
+        .filter_map(|name| {
+            let code = format!("1 {name}");
+            match context.interpret_with_settings(settings, &code, CodeSource::Internal) {
+                Ok((_, InterpreterResult::Value(Value::Quantity(unit)))) => {
+                    // numbat's base unit of mass is the gram, the SI one the kilogram.
+                    let base = unit.to_base_unit_representation();
+                    let mass_exponent: f64 = base
+                        .unit()
+                        .iter()
+                        .filter(|factor| factor.unit_id.name == "gram")
+                        .map(|factor| {
+                            *factor.exponent.numer() as f64 / *factor.exponent.denom() as f64
+                        })
+                        .sum();
+                    let si_factor = 1000f64.powf(mass_exponent);
+                    ((base.unsafe_value().to_f64() / si_factor - 1.0).abs() < 1e-12)
+                        .then_some(Value::Quantity(unit))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    // Several can fit (`Hz` and `Bq`); prefer the familiar one.
+    units.sort_by_key(|unit| match unit {
+        Value::Quantity(q) => {
+            let name = q.unit().to_string();
+            COMMON_UNITS
+                .iter()
+                .position(|common| *common == name)
+                .unwrap_or(usize::MAX)
+        }
+        _ => usize::MAX,
+    });
+    units.into_iter().next()
+}
+
+/// The module each name is defined in, from the sources of the modules
+/// `importer` knows; the first module (by path) wins.
+fn module_definitions(importer: &dyn ModuleImporter) -> HashMap<String, String> {
+    let mut modules = importer.list_modules();
+    modules.sort();
+    let mut defined_in = HashMap::new();
+    for module in modules {
+        let Some((source, _)) = importer.import(&module) else {
+            continue;
+        };
+        for name in syntax::dependencies(&source).0 {
+            defined_in
+                .entry(name.to_owned())
+                .or_insert_with(|| module.to_string());
+        }
+    }
+    defined_in
+}
+
+/// The units, functions, constants and dimensions `context` defines, with
+/// their modules, sorted by module (the user's first) — leaving out those
+/// of the prelude when given the `builtins` that have them. Reads nothing
+/// but the modules' sources, so it works offline.
+fn catalog_items(context: &Context, builtins: Option<&Builtins>) -> Vec<CatalogItem> {
+    let known = |kind: NameKind, name: &str| {
+        builtins.is_some_and(|builtins| builtins.names.contains(&(kind, name.to_owned())))
+    };
+    let from_module = |source: &CodeSource| matches!(source, CodeSource::Module(..));
+    let module_of = |source: CodeSource| match source {
+        CodeSource::Module(path, _) => path.to_string(),
+        _ => USER_MODULE.to_owned(),
+    };
+    let mut items = Vec::new();
+
+    for (name, (_, meta)) in context.unit_representations() {
+        let source = context.resolver().get_code_source(meta.code_source_id);
+        if from_module(&source) && known(NameKind::Unit, &name) {
+            continue;
+        }
+        let aliases: Vec<String> = meta
+            .aliases
+            .iter()
+            .map(|(alias, _)| alias.to_string())
+            .filter(|alias| *alias != name)
+            .collect();
+        items.push(CatalogItem {
+            example: format!(
+                "1 {}",
+                aliases.first().map_or(name.as_str(), |a| a.as_str())
+            ),
+            name: name.to_string(),
+            kind: NameKind::Unit,
+            module: module_of(source),
+            detail: plain_text_format(&meta.readable_type, false).to_string(),
+            aliases,
+        });
+    }
+
+    for function in context.functions() {
+        if from_module(&function.code_source) && known(NameKind::Function, &function.fn_name) {
+            continue;
+        }
+        // Without an example, there's no telling what to call it with.
+        let example = match function.examples.first() {
+            Some((code, _)) => code.to_string(),
+            None => function.fn_name.to_string(),
+        };
+        items.push(CatalogItem {
+            name: function.fn_name.to_string(),
+            kind: NameKind::Function,
+            module: module_of(function.code_source),
+            detail: function.signature_str.to_string(),
+            aliases: Vec::new(),
+            example,
+        });
+    }
+
+    // Constants and dimensions don't know where they come from: those of
+    // the prelude are looked up in the modules' sources, the others are the
+    // user's.
+    let defined_in = match builtins {
+        None => module_definitions(context.resolver().get_importer()),
+        Some(_) => HashMap::new(),
+    };
+    let module_of = |name: &str| {
+        defined_in
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| USER_MODULE.to_owned())
+    };
+    let mut scratch: Option<Context> = None;
+    for name in context.variable_names() {
+        if known(NameKind::Variable, &name) {
+            continue;
+        }
+        let scratch = scratch.get_or_insert_with(|| context.clone());
+        let detail = match scratch.interpret(&name, CodeSource::Internal) {
+            Ok((statements, _)) => statements
+                .last()
+                .and_then(value_type)
+                .map(|type_| {
+                    let readable = type_.to_readable_type(scratch.dimension_registry());
+                    plain_text_format(&readable, false).to_string()
+                })
+                .unwrap_or_default(),
+            Err(_) => String::new(),
+        };
+        items.push(CatalogItem {
+            module: module_of(&name),
+            name: name.to_string(),
+            kind: NameKind::Variable,
+            detail,
+            aliases: Vec::new(),
+            example: name.to_string(),
+        });
+    }
+    for name in context.dimension_names() {
+        if known(NameKind::Dimension, name) {
+            continue;
+        }
+        items.push(CatalogItem {
+            module: module_of(name),
+            name: name.to_string(),
+            kind: NameKind::Dimension,
+            detail: String::new(),
+            aliases: Vec::new(),
+            example: name.to_string(),
+        });
+    }
+
+    items.sort_by(|a, b| {
+        (a.module != USER_MODULE, &a.module, a.name.to_lowercase()).cmp(&(
+            b.module != USER_MODULE,
+            &b.module,
+            b.name.to_lowercase(),
+        ))
+    });
+    items
+}
+
+/// What a fresh context defines, gathered once, as that takes a while.
+struct Builtins {
+    catalog: Vec<CatalogItem>,
+    names: HashSet<(NameKind, String)>,
+}
+
+fn builtins() -> &'static Builtins {
+    static BUILTINS: OnceLock<Builtins> = OnceLock::new();
+    BUILTINS.get_or_init(|| {
+        let catalog = catalog_items(&fresh_context(), None);
+        let names = catalog
+            .iter()
+            .map(|item| (item.kind, item.name.clone()))
+            .collect();
+        Builtins { catalog, names }
+    })
+}
+
+/// The units, functions, constants and dimensions of the prelude, sorted
+/// by module; `Engine::user_catalog` has the rest.
+pub fn builtin_catalog() -> &'static [CatalogItem] {
+    &builtins().catalog
+}
+
 fn fresh_context() -> Context {
     let importer = ChainedImporter::new(
         Box::new(FileSystemImporter::default()),
@@ -184,6 +1062,40 @@ mod tests {
         Engine::new(FormatOptions::default())
     }
 
+    #[test]
+    fn catalogs_units_functions_and_constants_by_module() {
+        let mut e = engine();
+        e.eval("let r = 2 m");
+        e.eval("unit blorp = 1.7018 m");
+        e.eval("fn double(x: Length) -> Length = 2 x");
+        e.eval("use extra::algebra");
+        let user = e.user_catalog();
+        let item = |name: &str| {
+            user.iter()
+                .chain(builtin_catalog())
+                .find(|item| item.name == name)
+                .unwrap_or_else(|| panic!("{name} missing"))
+        };
+        assert_eq!(user[0].module, USER_MODULE);
+        assert_eq!(item("r").module, USER_MODULE);
+        assert_eq!(item("r").detail, "Length");
+        assert_eq!(item("blorp").kind, NameKind::Unit);
+        assert_eq!(item("blorp").module, USER_MODULE);
+        assert_eq!(item("double").example, "double");
+        // Imported modules are the session's too; the prelude isn't.
+        assert!(user
+            .iter()
+            .any(|item| item.name == "quadratic_equation" && item.module == "extra::algebra"));
+        assert!(!user.iter().any(|item| item.name == "metre"));
+        assert_eq!(item("metre").module, "units::si");
+        assert!(item("metre").aliases.iter().any(|alias| alias == "m"));
+        assert_eq!(item("speed_of_light").module, "physics::constants");
+        assert_eq!(item("speed_of_light").detail, "Velocity");
+        assert!(item("sqrt").detail.starts_with("fn sqrt"));
+        assert_eq!(item("Length").kind, NameKind::Dimension);
+        assert!(item("Length").module.starts_with("core::"));
+    }
+
     #[test]
     fn evaluates_simple_expression() {
         let mut e = engine();
@@ -217,8 +1129,110 @@ mod tests {
     #[test]
     fn preview_returns_value() {
         let e = engine();
-        let (_, plain) = e.preview("6 * 7").unwrap();
-        assert_eq!(plain, "42");
+        let preview = e.preview("6 * 7").unwrap();
+        assert_eq!(preview.plain, "42");
+        assert!(preview.conversions.is_empty());
+    }
+
+    #[test]
+    fn offers_conversions_of_the_same_dimension() {
+        let mut e = engine();
+        let out = e.eval("4.2 km");
+        let names: Vec<&str> = out.conversions.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names[..4], ["m", "mi", "ft", "nmi"]);
+        assert!(names.contains(&"au"));
+        assert!(!names.contains(&"s"));
+
+        let out = e.eval("3 ft");
+        assert!(!out.conversions.iter().any(|c| c.name == "ft"));
+    }
+
+    #[test]
+    fn shows_results_in_the_preferred_units() {
+        let mut e = engine().with_preferred_units(PreferredUnits::Imperial);
+        let out = e.eval("4.2 km");
+        assert_eq!(out.result_plain.as_deref(), Some("2.60976 mi"));
+        assert_eq!(
+            out.original.map(|(_, plain)| plain).as_deref(),
+            Some("4.2 km")
+        );
+        assert_eq!(e.eval("30 cm").result_plain.as_deref(), Some("11.811 in"));
+        assert!(e.eval("3 yd").original.is_none());
+        assert!(e.eval("4.2 km -> m").original.is_none());
+        assert!(e.eval("2 * 3").original.is_none());
+
+        e.preferred_units = PreferredUnits::Si;
+        assert_eq!(e.eval("3 ft").result_plain.as_deref(), Some("91.44 cm"));
+        assert!(e.eval("4.2 km").original.is_none());
+        assert_eq!(e.preview("3 ft").unwrap().plain, "91.44 cm");
+
+        let targets = [("energy".to_owned(), "kWh".to_owned())].into();
+        e.preferred_units = PreferredUnits::Custom(targets);
+        assert_eq!(e.eval("1.8 MJ").result_plain.as_deref(), Some("0.5 kWh"));
+        assert!(e.eval("3 bar").original.is_none());
+    }
+
+    #[test]
+    fn names_the_dimension_of_a_unit() {
+        let e = engine();
+        assert_eq!(e.dimension_of("kWh").unwrap(), "Energy or Torque");
+        assert_eq!(e.dimension_of("mi").unwrap(), "Length");
+        assert!(e.dimension_of("parsnip").is_err());
+    }
+
+    #[test]
+    fn sweeps_without_side_effects() {
+        let mut e = engine();
+        e.eval("let mass = 2 kg");
+        let sweep = e
+            .sweep("v", "10 m/s", "50 m/s", 5, "0.5 mass v^2 -> J")
+            .unwrap();
+        let values: Vec<&str> = sweep.rows.iter().map(|(v, _)| v.as_str()).collect();
+        assert_eq!(values, ["10 m/s", "20 m/s", "30 m/s", "40 m/s", "50 m/s"]);
+        assert_eq!(sweep.rows[4].1.as_deref(), Ok("2500 J"));
+        assert!(e.eval("v").error.is_some(), "the variable must not leak");
+        assert_eq!(
+            sweep.to_csv().lines().take(2).collect::<Vec<_>>(),
+            ["v,0.5 mass v^2 -> J", "10 m/s,100 J"]
+        );
+
+        let failing = e.sweep("v", "1 m", "2 m", 2, "v + 1 s").unwrap();
+        assert_eq!(
+            failing.rows[0].1,
+            Err("Incompatible dimensions in addition".to_owned()),
+        );
+        assert!(e.sweep("v", "1 m", "2 s", 2, "v").is_err());
+    }
+
+    #[test]
+    fn plots_functions_and_expressions() {
+        let mut e = engine();
+        e.eval("fn drag(v: Velocity) -> Force = 0.5 * 1.2 kg/m^3 * v^2 * 0.3 * 2 m^2");
+        let series = [
+            "drag".to_owned(),
+            "x * 1 kg / s".to_owned(),
+            "x * 2 s".to_owned(),
+        ];
+        let plot = e.plot(&series, "0 km/h", "100 km/h", 11).unwrap();
+        assert_eq!(plot.x_unit, "km/h");
+        assert_eq!(plot.y_unit, "N");
+        let drag = plot.series[0].as_ref().unwrap();
+        assert_eq!(drag.len(), 11);
+        assert_eq!(drag[10][0], 100.0);
+        assert!((drag[10][1] - 277.78).abs() < 0.01, "{:?}", drag[10]);
+        let in_newtons = e
+            .plot(&["drag(x) -> N".to_owned()], "0 km/h", "100 km/h", 11)
+            .unwrap();
+        assert_eq!(in_newtons.y_unit, "N");
+        assert!((in_newtons.series[0].as_ref().unwrap()[10][1] - 277.78).abs() < 0.01);
+        assert!(plot.series[1].is_ok());
+        assert!(plot.series[2].is_err(), "a length is not a force");
+
+        assert!(e.plot(&series, "10 km/h", "3 s", 11).is_err());
+        assert!(
+            e.plot(&["x^2".to_owned()], "0", "2", 3).unwrap().series[0]
+                == Ok(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 4.0]])
+        );
     }
 
     #[test]
@@ -1,79 +1,315 @@
-//! System-wide hotkey that summons the quick panel.
+//! System-wide hotkeys: summon the quick panel, show the main window, copy
+//! the last result, or evaluate the clipboard. All share one manager and
+//! one listener thread.
 
 use std::str::FromStr;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use global_hotkey::hotkey::HotKey;
 use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
 
-pub struct QuickPanelHotkey {
+use crate::config::UiConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlobalAction {
+    ToggleQuickPanel,
+    ShowMainWindow,
+    CopyLastResult,
+    EvaluateClipboard,
+}
+
+impl GlobalAction {
+    pub const ALL: [Self; 4] = [
+        Self::ToggleQuickPanel,
+        Self::ShowMainWindow,
+        Self::CopyLastResult,
+        Self::EvaluateClipboard,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ToggleQuickPanel => "Quick panel",
+            Self::ShowMainWindow => "Main window",
+            Self::CopyLastResult => "Copy last result",
+            Self::EvaluateClipboard => "Evaluate clipboard",
+        }
+    }
+
+    /// The configured combo; empty when the action has no hotkey.
+    pub fn combo<'a>(&self, config: &'a UiConfig) -> &'a str {
+        match self {
+            Self::ToggleQuickPanel => &config.quick_panel_hotkey,
+            Self::ShowMainWindow => &config.main_window_hotkey,
+            Self::CopyLastResult => &config.copy_result_hotkey,
+            Self::EvaluateClipboard => &config.evaluate_clipboard_hotkey,
+        }
+    }
+
+    pub fn combo_mut<'a>(&self, config: &'a mut UiConfig) -> &'a mut String {
+        match self {
+            Self::ToggleQuickPanel => &mut config.quick_panel_hotkey,
+            Self::ShowMainWindow => &mut config.main_window_hotkey,
+            Self::CopyLastResult => &mut config.copy_result_hotkey,
+            Self::EvaluateClipboard => &mut config.evaluate_clipboard_hotkey,
+        }
+    }
+}
+
+pub struct GlobalHotkeys {
     manager: GlobalHotKeyManager,
-    registered: Option<HotKey>,
-    /// Set by the listener thread when the hotkey fires; drained by the app.
-    pressed: Arc<AtomicBool>,
+    registered: Vec<(GlobalAction, HotKey)>,
+    /// Ids of the hotkeys pressed, pushed by the listener thread; drained
+    /// by the app.
+    pressed: Arc<Mutex<Vec<u32>>>,
 }
 
-impl QuickPanelHotkey {
-    /// Creates the manager and registers `combo`. Must be called on the main
-    /// thread (macOS requirement). A repaint of the ROOT viewport is
-    /// requested whenever the hotkey fires, so the app wakes up even while
-    /// hidden. (Explicitly ROOT: a plain `request_repaint()` from this
-    /// thread can race into a pass of the quick-panel viewport and target
-    /// it instead — and eframe silently drops repaint requests for viewports
-    /// that no longer exist, losing the hotkey press.)
-    pub fn new(combo: &str, ctx: egui::Context) -> Result<Self, String> {
+impl GlobalHotkeys {
+    /// Creates the manager and registers the hotkeys of `config`. Must be
+    /// called on the main thread (macOS requirement). A repaint of the ROOT
+    /// viewport is requested whenever a hotkey fires, so the app wakes up
+    /// even while hidden. (Explicitly ROOT: a plain `request_repaint()` from
+    /// this thread can race into a pass of the quick-panel viewport and
+    /// target it instead — and eframe silently drops repaint requests for
+    /// viewports that no longer exist, losing the hotkey press.)
+    ///
+    /// Fails only if the manager can't be created; hotkeys that can't be
+    /// registered are reported in the second value.
+    pub fn new(config: &UiConfig, ctx: egui::Context) -> Result<(Self, Option<String>), String> {
         let manager = GlobalHotKeyManager::new()
             .map_err(|e| format!("Failed to initialize global hotkeys: {e}"))?;
 
-        let pressed = Arc::new(AtomicBool::new(false));
+        let pressed = Arc::new(Mutex::new(Vec::new()));
         {
             let pressed = Arc::clone(&pressed);
             std::thread::spawn(move || {
                 while let Ok(event) = GlobalHotKeyEvent::receiver().recv() {
                     if event.state() == HotKeyState::Pressed {
-                        log::debug!("Global hotkey pressed; requesting repaint");
-                        pressed.store(true, Ordering::SeqCst);
+                        log::debug!("Global hotkey {} pressed; requesting repaint", event.id());
+                        if let Ok(mut pressed) = pressed.lock() {
+                            pressed.push(event.id());
+                        }
                         ctx.request_repaint_of(egui::ViewportId::ROOT);
                     }
                 }
             });
         }
 
-        let mut hotkey = Self {
+        let mut hotkeys = Self {
             manager,
-            registered: None,
+            registered: Vec::new(),
             pressed,
         };
-        hotkey.register(combo)?;
-        Ok(hotkey)
+        let error = hotkeys.register_all(config);
+        Ok((hotkeys, error))
     }
 
-    /// Replaces the registered combo. On failure the old combo stays active.
-    pub fn register(&mut self, combo: &str) -> Result<(), String> {
+    /// Registers the hotkeys of `config`, keeping those that didn't change.
+    /// Like `register`, a new combo is taken before the old one is
+    /// released, so one that can't be had leaves the old one active.
+    /// Returns the errors, if any.
+    pub fn register_all(&mut self, config: &UiConfig) -> Option<String> {
+        let mut errors = Vec::new();
+        // Actions whose new combo another of ours still holds; they move
+        // once that one moved.
+        let mut waiting: Vec<GlobalAction> = GlobalAction::ALL.to_vec();
+        while !waiting.is_empty() {
+            let ready = waiting
+                .iter()
+                .position(|action| !self.held_by_another(*action, action.combo(config)));
+            if let Some(index) = ready {
+                let action = waiting.remove(index);
+                if let Err(e) = self.register(action, action.combo(config)) {
+                    errors.push(e);
+                }
+                continue;
+            }
+            // Combos swapped between actions: release one of them so the
+            // others can move.
+            let wanted: Vec<HotKey> = waiting
+                .iter()
+                .filter_map(|action| parse_combo(action.combo(config)).ok())
+                .collect();
+            let swapped = waiting.iter().find_map(|action| {
+                let held = self.registered(*action)?;
+                wanted.contains(&held).then_some((*action, held))
+            });
+            match swapped {
+                Some((action, held)) => {
+                    let _ = self.manager.unregister(held);
+                    self.registered.retain(|(a, _)| *a != action);
+                }
+                None => {
+                    for action in waiting.drain(..) {
+                        let combo = action.combo(config);
+                        errors.push(format!(
+                            "Failed to register \"{combo}\": another action still uses it"
+                        ));
+                    }
+                }
+            }
+        }
+        (!errors.is_empty()).then(|| errors.join("\n"))
+    }
+
+    /// Replaces the combo of `action`; an empty combo removes its hotkey.
+    /// On failure the old combo stays active.
+    pub fn register(&mut self, action: GlobalAction, combo: &str) -> Result<(), String> {
+        let current = self.registered(action);
+        if combo.trim().is_empty() {
+            if let Some(old) = current {
+                let _ = self.manager.unregister(old);
+                self.registered.retain(|(a, _)| *a != action);
+            }
+            return Ok(());
+        }
         let hotkey = parse_combo(combo)?;
-        if self.registered == Some(hotkey) {
+        if current == Some(hotkey) {
             return Ok(());
         }
         self.manager
             .register(hotkey)
             .map_err(|e| format!("Failed to register \"{combo}\": {e}"))?;
-        if let Some(old) = self.registered.replace(hotkey) {
+        if let Some(old) = current {
             let _ = self.manager.unregister(old);
         }
+        self.registered.retain(|(a, _)| *a != action);
+        self.registered.push((action, hotkey));
+        Ok(())
+    }
+
+    /// Whether an action other than `action` holds the hotkey of `combo`.
+    fn held_by_another(&self, action: GlobalAction, combo: &str) -> bool {
+        parse_combo(combo).is_ok_and(|hotkey| {
+            self.registered
+                .iter()
+                .any(|(a, h)| *a != action && *h == hotkey)
+        })
+    }
+
+    fn registered(&self, action: GlobalAction) -> Option<HotKey> {
+        self.registered
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, hotkey)| *hotkey)
+    }
+
+    /// Checks that `combo` can be registered — that it is valid and not
+    /// taken by another app — without keeping it. Our own hotkeys count as
+    /// available.
+    pub fn check(&self, combo: &str) -> Result<(), String> {
+        let hotkey = parse_combo(combo)?;
+        if self.registered.iter().any(|(_, h)| *h == hotkey) {
+            return Ok(());
+        }
+        self.manager
+            .register(hotkey)
+            .map_err(|e| format!("Cannot use {}: {e}", display_combo(combo)))?;
+        let _ = self.manager.unregister(hotkey);
         Ok(())
     }
 
-    /// True once per hotkey press.
-    pub fn take_pressed(&self) -> bool {
-        self.pressed.swap(false, Ordering::SeqCst)
+    /// The actions whose hotkeys were pressed since the last call.
+    pub fn take_pressed(&self) -> Vec<GlobalAction> {
+        let ids = match self.pressed.lock() {
+            Ok(mut pressed) => std::mem::take(&mut *pressed),
+            Err(_) => return Vec::new(),
+        };
+        ids.into_iter()
+            .filter_map(|id| {
+                self.registered
+                    .iter()
+                    .find(|(_, hotkey)| hotkey.id() == id)
+                    .map(|(action, _)| *action)
+            })
+            .collect()
     }
 }
 
+/// The text to evaluate for `GlobalAction::EvaluateClipboard`: the
+/// clipboard, or on X11 optionally the PRIMARY selection.
+pub fn read_clipboard(primary_selection: bool) -> Result<String, String> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| format!("Cannot read the clipboard: {e}"))?;
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if primary_selection {
+        use arboard::{GetExtLinux, LinuxClipboardKind};
+        return clipboard
+            .get()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text()
+            .map_err(|e| format!("Cannot read the selection: {e}"));
+    }
+    let _ = primary_selection;
+    clipboard
+        .get_text()
+        .map_err(|e| format!("Cannot read the clipboard: {e}"))
+}
+
+/// Combos bound to more than one global action.
+pub fn duplicate_combos(config: &UiConfig) -> Vec<String> {
+    let hotkeys: Vec<(HotKey, &str)> = GlobalAction::ALL
+        .iter()
+        .map(|action| action.combo(config))
+        .filter(|combo| !combo.trim().is_empty())
+        .filter_map(|combo| Some((parse_combo(combo).ok()?, combo)))
+        .collect();
+    let mut duplicates: Vec<String> = Vec::new();
+    for (i, (hotkey, combo)) in hotkeys.iter().enumerate() {
+        if hotkeys[..i].iter().any(|(h, _)| h == hotkey)
+            && !duplicates
+                .iter()
+                .any(|d| parse_combo(d).ok() == Some(*hotkey))
+        {
+            duplicates.push((*combo).to_owned());
+        }
+    }
+    duplicates
+}
+
 pub fn parse_combo(combo: &str) -> Result<HotKey, String> {
     HotKey::from_str(combo.trim()).map_err(|e| format!("Invalid hotkey \"{combo}\": {e}"))
 }
 
+/// The combo for a key press recorded in the settings, in `global_hotkey`
+/// syntax. Needs Cmd, Ctrl or Alt, except for function keys — a global
+/// hotkey on a plain key would swallow it in every other app.
+pub fn combo_from_press(modifiers: egui::Modifiers, key: egui::Key) -> Result<String, String> {
+    use egui::Key;
+
+    let key_name = match key {
+        Key::Equals => "Equal",
+        Key::Backtick => "Backquote",
+        Key::OpenBracket => "BracketLeft",
+        Key::CloseBracket => "BracketRight",
+        _ => key.name(),
+    };
+    let function_key = key_name
+        .strip_prefix('F')
+        .is_some_and(|n| n.parse::<u8>().is_ok());
+
+    let mut parts = Vec::new();
+    if modifiers.mac_cmd {
+        parts.push("Cmd");
+    }
+    if modifiers.ctrl {
+        parts.push("Ctrl");
+    }
+    if modifiers.alt {
+        parts.push("Alt");
+    }
+    if modifiers.shift {
+        parts.push("Shift");
+    }
+    if !(modifiers.mac_cmd || modifiers.ctrl || modifiers.alt || function_key) {
+        return Err("Add Cmd, Ctrl or Alt — a plain key can't be a global hotkey".to_owned());
+    }
+    parts.push(key_name);
+
+    let combo = parts.join("+");
+    parse_combo(&combo).map_err(|_| format!("{} can't be a global hotkey", key.name()))?;
+    Ok(combo)
+}
+
 /// Human-readable form of a combo for hints, e.g. "Opt+Space" on macOS.
 /// (Plain text: egui's default fonts have no ⌘/⌥/⇧ glyphs.)
 pub fn display_combo(combo: &str) -> String {
@@ -113,6 +349,40 @@ mod tests {
         assert!(parse_combo("").is_err());
     }
 
+    #[test]
+    fn converts_key_presses() {
+        use egui::{Key, Modifiers};
+        let combo = |m, k| combo_from_press(m, k);
+        assert_eq!(
+            combo(Modifiers::CTRL | Modifiers::ALT, Key::Space).as_deref(),
+            Ok("Ctrl+Alt+Space")
+        );
+        assert_eq!(
+            combo(Modifiers::ALT | Modifiers::SHIFT, Key::K).as_deref(),
+            Ok("Alt+Shift+K")
+        );
+        assert_eq!(
+            combo(Modifiers::CTRL, Key::OpenBracket).as_deref(),
+            Ok("Ctrl+BracketLeft")
+        );
+        assert_eq!(combo(Modifiers::NONE, Key::F5).as_deref(), Ok("F5"));
+        assert!(combo(Modifiers::NONE, Key::K).is_err());
+        assert!(combo(Modifiers::SHIFT, Key::K).is_err());
+        for m in [Modifiers::CTRL, Modifiers::ALT] {
+            let combo = combo(m, Key::Num1).unwrap();
+            assert!(parse_combo(&combo).is_ok());
+        }
+    }
+
+    #[test]
+    fn finds_duplicate_combos() {
+        let mut config = UiConfig::default();
+        assert!(duplicate_combos(&config).is_empty());
+        config.copy_result_hotkey = "Ctrl+Alt+C".to_owned();
+        config.evaluate_clipboard_hotkey = "ctrl+alt+KeyC".to_owned();
+        assert_eq!(duplicate_combos(&config), ["ctrl+alt+KeyC"]);
+    }
+
     #[test]
     fn display_is_readable() {
         let shown = display_combo("Alt+Space");
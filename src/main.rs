@@ -1,5 +1,6 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod actions;
 mod app;
 mod autostart;
 mod config;
@@ -7,6 +8,7 @@ mod engine;
 mod hotkey;
 mod platform;
 mod session;
+mod syntax;
 mod theme;
 mod ui;
 
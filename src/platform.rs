@@ -1,9 +1,16 @@
-//! Platform-specific integration: the native macOS menu bar and a
-//! workaround for macOS dead keys in math input.
+//! Platform-specific integration: the native macOS menu bar, a
+//! workaround for macOS dead keys in math input, and finding the monitor
+//! under the mouse (macOS, X11 and Windows) for the quick panel.
 
 #[cfg(target_os = "macos")]
 pub use macos::*;
 
+#[cfg(target_os = "linux")]
+pub use x11::*;
+
+#[cfg(target_os = "windows")]
+pub use windows::*;
+
 #[cfg(target_os = "macos")]
 mod macos {
     use muda::accelerator::{Accelerator, Code, Modifiers};
@@ -150,6 +157,18 @@ mod macos {
         None
     }
 
+    /// The mouse cursor position, in points with a top-left origin.
+    pub fn mouse_position() -> Option<egui::Pos2> {
+        use objc2_app_kit::{NSEvent, NSScreen};
+        let mtm = objc2::MainThreadMarker::new()?;
+        let mouse = NSEvent::mouseLocation();
+        let primary_height = NSScreen::screens(mtm).iter().next()?.frame().size.height;
+        Some(egui::pos2(
+            mouse.x as f32,
+            (primary_height - mouse.y) as f32,
+        ))
+    }
+
     /// Calls `on_activate` (on the main thread) whenever the app becomes
     /// active — e.g. it is re-opened through the Finder, Spotlight or the
     /// Dock while already running in the background.
@@ -475,3 +494,199 @@ mod macos {
         raw_input.events = new_events;
     }
 }
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::randr::ConnectionExt as _;
+    use x11rb::protocol::xproto::ConnectionExt as _;
+
+    /// The frame of the monitor the mouse cursor is on, in physical pixels
+    /// of the X screen (divide by the scale factor for points). Uses the
+    /// RandR 1.5 monitor list, falling back to the whole X screen. `None`
+    /// without an X server, e.g. on a pure Wayland session.
+    pub fn screen_rect_under_mouse() -> Option<egui::Rect> {
+        let (conn, screen) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots.get(screen)?;
+        let pointer = conn.query_pointer(root.root).ok()?.reply().ok()?;
+        let mouse = egui::pos2(pointer.root_x as f32, pointer.root_y as f32);
+
+        let monitors: Vec<egui::Rect> = conn
+            .randr_get_monitors(root.root, true)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| {
+                reply
+                    .monitors
+                    .iter()
+                    .map(|m| {
+                        egui::Rect::from_min_size(
+                            egui::pos2(m.x as f32, m.y as f32),
+                            egui::vec2(m.width as f32, m.height as f32),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        monitor_at(&monitors, mouse).or_else(|| {
+            Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(root.width_in_pixels as f32, root.height_in_pixels as f32),
+            ))
+        })
+    }
+
+    /// The mouse cursor position, in physical pixels of the X screen.
+    pub fn mouse_position() -> Option<egui::Pos2> {
+        let (conn, screen) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots.get(screen)?.root;
+        let pointer = conn.query_pointer(root).ok()?.reply().ok()?;
+        Some(egui::pos2(pointer.root_x as f32, pointer.root_y as f32))
+    }
+
+    /// The monitor containing `point`, if any. Monitors may overlap when
+    /// outputs are mirrored; the first one wins.
+    fn monitor_at(monitors: &[egui::Rect], point: egui::Pos2) -> Option<egui::Rect> {
+        monitors
+            .iter()
+            .find(|m| {
+                point.x >= m.left()
+                    && point.x < m.right()
+                    && point.y >= m.top()
+                    && point.y < m.bottom()
+            })
+            .copied()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use x11rb::protocol::randr::MonitorInfo;
+
+        fn rect(x: f32, y: f32, w: f32, h: f32) -> egui::Rect {
+            egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(w, h))
+        }
+
+        #[test]
+        fn finds_the_monitor_under_a_point() {
+            let monitors = [
+                rect(0.0, 0.0, 1920.0, 1080.0),
+                rect(1920.0, 0.0, 1280.0, 1024.0),
+            ];
+            assert_eq!(
+                monitor_at(&monitors, egui::pos2(100.0, 100.0)),
+                Some(monitors[0])
+            );
+            assert_eq!(
+                monitor_at(&monitors, egui::pos2(1920.0, 0.0)),
+                Some(monitors[1])
+            );
+            assert_eq!(monitor_at(&monitors, egui::pos2(2500.0, 1050.0)), None);
+        }
+
+        /// Run against a virtual display, e.g.
+        /// `Xvfb :99 -screen 0 3840x1080x24 & DISPLAY=:99 cargo test -- --ignored`.
+        /// Splits the screen into two RandR monitors and moves the pointer.
+        #[test]
+        #[ignore = "needs an X server (Xvfb)"]
+        fn follows_the_pointer_across_monitors() {
+            let (conn, screen) = x11rb::connect(None).expect("no X server");
+            let root = conn.setup().roots[screen].root;
+            let halves = [
+                (b"NUMBAT_LEFT".as_slice(), 0),
+                (b"NUMBAT_RIGHT".as_slice(), 1920),
+            ];
+            let mut names = Vec::new();
+            for (name, x) in halves {
+                let name = conn.intern_atom(false, name).unwrap().reply().unwrap().atom;
+                let monitor = MonitorInfo {
+                    name,
+                    primary: x == 0,
+                    automatic: false,
+                    x,
+                    y: 0,
+                    width: 1920,
+                    height: 1080,
+                    width_in_millimeters: 508,
+                    height_in_millimeters: 286,
+                    outputs: Vec::new(),
+                };
+                conn.randr_set_monitor(root, monitor)
+                    .unwrap()
+                    .check()
+                    .unwrap();
+                names.push(name);
+            }
+
+            for (x, expected) in [
+                (2500, rect(1920.0, 0.0, 1920.0, 1080.0)),
+                (300, rect(0.0, 0.0, 1920.0, 1080.0)),
+            ] {
+                conn.warp_pointer(x11rb::NONE, root, 0, 0, 0, 0, x, 500)
+                    .unwrap();
+                conn.get_input_focus().unwrap().reply().unwrap();
+                assert_eq!(screen_rect_under_mouse(), Some(expected));
+                assert_eq!(mouse_position(), Some(egui::pos2(x as f32, 500.0)));
+            }
+
+            for name in names {
+                conn.randr_delete_monitor(root, name)
+                    .unwrap()
+                    .check()
+                    .unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use windows_sys::Win32::Foundation::{POINT, RECT};
+    use windows_sys::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromPoint, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+    /// The frame of the monitor the mouse cursor is on, in physical pixels
+    /// of the virtual screen (divide by the scale factor for points).
+    /// Secondary monitors left of or above the primary one have negative
+    /// coordinates.
+    pub fn screen_rect_under_mouse() -> Option<egui::Rect> {
+        let cursor = cursor_pos()?;
+        let empty = RECT {
+            left: 0,
+            top: 0,
+            right: 0,
+            bottom: 0,
+        };
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            rcMonitor: empty,
+            rcWork: empty,
+            dwFlags: 0,
+        };
+        // SAFETY: `info` is a valid MONITORINFO with `cbSize` set.
+        let found = unsafe {
+            let monitor = MonitorFromPoint(cursor, MONITOR_DEFAULTTONEAREST);
+            !monitor.is_null() && GetMonitorInfoW(monitor, &mut info) != 0
+        };
+        let frame = info.rcMonitor;
+        found.then(|| {
+            egui::Rect::from_min_max(
+                egui::pos2(frame.left as f32, frame.top as f32),
+                egui::pos2(frame.right as f32, frame.bottom as f32),
+            )
+        })
+    }
+
+    /// The mouse cursor position, in physical pixels of the virtual screen.
+    pub fn mouse_position() -> Option<egui::Pos2> {
+        cursor_pos().map(|p| egui::pos2(p.x as f32, p.y as f32))
+    }
+
+    fn cursor_pos() -> Option<POINT> {
+        let mut point = POINT { x: 0, y: 0 };
+        // SAFETY: `point` is a valid out pointer.
+        (unsafe { GetCursorPos(&mut point) } != 0).then_some(point)
+    }
+}
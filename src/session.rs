@@ -2,21 +2,99 @@
 //! evaluated entries, the current input line, command history with
 //! navigation, the live preview cache, and history persistence.
 
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use numbat::markup::Markup;
+use numbat::compact_str::CompactString;
+use numbat::markup::{plain_text_format, Markup};
 
-use crate::engine::Engine;
+use crate::engine::{Engine, EngineState, Sweep, UnitChoice, CONVERSION_OPERATORS};
+use crate::syntax::{self, NameKind, Names};
 
 /// Maximum number of input lines persisted (and replayed on startup).
 const MAX_PERSISTED_HISTORY: usize = 200;
 
+/// Conversion chips offered under a result.
+const MAX_CONVERSIONS: usize = 4;
+
+/// How many clears, resets, deletions and refreshes can be undone.
+const MAX_UNDO: usize = 20;
+
+/// Most rows a sweep may have.
+const MAX_SWEEP_STEPS: usize = 1000;
+
+const SWEEP_USAGE: &str =
+    "usage: sweep <variable> from <start> to <end> in <n> steps: <expression>";
+
+#[derive(Clone)]
 pub struct HistoryEntry {
     pub input: String,
     pub printed: Vec<Markup>,
     pub result: Option<Markup>,
     pub result_plain: Option<String>,
+    /// Units to offer converting the result to.
+    pub conversions: Vec<String>,
+    /// For results shown in the preferred units: the value as computed
+    /// (after reverting: the converted value), to swap back and forth.
+    pub alternative: Option<(Markup, String)>,
+    /// Whether the result was reverted to the unit it was computed in.
+    pub reverted: bool,
+    /// The table of a `sweep` command.
+    pub sweep: Option<Sweep>,
     pub error: Option<String>,
+    /// The entry's line in the command history, where edits are written
+    /// back to.
+    pub history_line: Option<usize>,
+    /// Whether the result changed when an earlier definition was edited.
+    pub recomputed: bool,
+}
+
+impl HistoryEntry {
+    fn new(input: &str) -> Self {
+        Self {
+            input: input.to_owned(),
+            printed: Vec::new(),
+            result: None,
+            result_plain: None,
+            conversions: Vec::new(),
+            alternative: None,
+            reverted: false,
+            sweep: None,
+            error: None,
+            history_line: None,
+            recomputed: false,
+        }
+    }
+
+    /// What copying the entry copies: its result, a sweep table as CSV, or
+    /// the output of a command like `list`.
+    pub fn copy_text(&self) -> Option<String> {
+        if let Some(result) = &self.result_plain {
+            return Some(result.clone());
+        }
+        if let Some(sweep) = &self.sweep {
+            return Some(sweep.to_csv());
+        }
+        let printed: Vec<String> = self
+            .printed
+            .iter()
+            .map(|markup| plain_text_format(markup, false).trim().to_owned())
+            .filter(|text| !text.is_empty())
+            .collect();
+        (!printed.is_empty()).then(|| printed.join("\n"))
+    }
+
+    /// Swaps between the result in the preferred units and as computed.
+    pub fn toggle_preferred_units(&mut self) {
+        let Some((markup, plain)) = self.alternative.take() else {
+            return;
+        };
+        let result = self.result.replace(markup);
+        let result_plain = self.result_plain.replace(plain);
+        self.alternative = result.zip(result_plain);
+        self.reverted = !self.reverted;
+    }
 }
 
 pub struct Session {
@@ -33,17 +111,88 @@ pub struct Session {
     nav_stash: String,
 
     /// Cache for the live preview: (input it was computed for, result).
-    preview_cache: Option<(String, Option<(Markup, String)>)>,
+    preview_cache: Option<(String, Option<Preview>)>,
     /// The most recent *valid* preview, kept while typing so the preview
     /// doesn't flicker away every time the input is momentarily incomplete.
-    last_good_preview: Option<(Markup, String)>,
+    last_good_preview: Option<Preview>,
+
+    /// How often each unit was converted to, to rank conversion chips.
+    conversion_counts: HashMap<String, usize>,
+
+    /// Names defined in the engine, for highlighting; `None` after anything
+    /// was evaluated (rebuilt lazily, as replaying history evaluates a lot).
+    names: Option<Arc<Names>>,
+    /// What the session defined, for the inspector, and the names it was
+    /// collected for (so it's rebuilt along with them).
+    definitions: Option<(Arc<Names>, Arc<Vec<Definition>>)>,
+
+    /// Saved with `checkpoint`, in order; `restore` rolls back to one.
+    pub checkpoints: Vec<Checkpoint>,
+
+    /// The session before each clear, reset, deletion or refresh since
+    /// anything else changed it; `undo` pops the latest.
+    undo_stack: Vec<UndoStep>,
+    next_undo_id: u64,
+    /// The id and notice of a step that can be undone, for the UI to show
+    /// (with an Undo button) and take.
+    pub undo_notice: Option<(u64, String)>,
+}
+
+/// A name the session defined, for the inspector.
+pub struct Definition {
+    pub name: String,
+    pub kind: NameKind,
+    /// A variable's value, a function's signature, a unit's dimension or
+    /// what a dimension is defined as.
+    pub detail: String,
+    /// The value of a variable defined by a plain `let`, as a number and
+    /// its unit, which `redefine` can change.
+    pub quantity: Option<(f64, String)>,
+}
+
+/// What a checkpoint or undo returns to: the engine's definitions, the
+/// entries and the command history.
+#[derive(Clone)]
+struct Snapshot {
+    state: EngineState,
+    entries: Vec<HistoryEntry>,
+    cmd_history: Vec<String>,
+}
+
+/// What `undo` takes back.
+struct UndoStep {
+    id: u64,
+    /// What happened, e.g. "History cleared".
+    notice: String,
+    snapshot: Snapshot,
+    /// The engine `refresh` replaced, with its formatting options.
+    engine: Option<Engine>,
+}
+
+/// The session at the time of a `checkpoint` command. Checkpoints aren't
+/// saved themselves, but replaying the history on startup makes them again.
+pub struct Checkpoint {
+    pub name: String,
+    snapshot: Snapshot,
+}
+
+/// The later entries re-run after an entry was edited in place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Replay {
+    /// None: they keep showing what they showed.
+    Edited,
+    /// Those using a name whose definition changed (reactive mode).
+    Dependents,
+    All,
 }
 
 /// A live preview of the current input. `fresh` is false when the shown
 /// value belongs to an earlier (valid) version of the input.
+#[derive(Clone)]
 pub struct Preview {
     pub markup: Markup,
     pub plain: String,
+    pub conversions: Vec<String>,
     pub fresh: bool,
 }
 
@@ -59,6 +208,13 @@ impl Session {
             nav_stash: String::new(),
             preview_cache: None,
             last_good_preview: None,
+            conversion_counts: HashMap::new(),
+            names: None,
+            definitions: None,
+            checkpoints: Vec::new(),
+            undo_stack: Vec::new(),
+            next_undo_id: 0,
+            undo_notice: None,
         }
     }
 
@@ -73,60 +229,367 @@ impl Session {
             return;
         }
 
-        self.cmd_history.push(line.clone());
+        match line.as_str() {
+            "clear" if !self.entries.is_empty() => {
+                self.push_undo("History cleared");
+            }
+            "reset" => {
+                self.push_undo("Session reset");
+            }
+            // Undoing an earlier step would take this line back too.
+            _ => self.forget_undo(),
+        }
+        self.push_history(line.clone());
         self.run_line(&line);
         self.scroll_to_bottom = true;
         self.persist_history();
     }
 
+    /// Submits `line` as if typed, keeping whatever is being typed.
+    pub fn submit_line(&mut self, line: String) {
+        let draft = std::mem::replace(&mut self.input, line);
+        self.submit();
+        self.input = draft;
+    }
+
+    fn push_history(&mut self, line: String) {
+        if let Some((_, unit)) = split_conversion(&line) {
+            *self.conversion_counts.entry(unit.to_owned()).or_default() += 1;
+        }
+        self.cmd_history.push(line);
+    }
+
+    /// The names of the units to offer, the ones converted to most often
+    /// first.
+    fn rank_conversions(&self, choices: Vec<UnitChoice>) -> Vec<String> {
+        let uses = |choice: &UnitChoice| -> usize {
+            choice
+                .aliases
+                .iter()
+                .filter_map(|alias| self.conversion_counts.get(alias))
+                .sum()
+        };
+        let mut ranked: Vec<(usize, UnitChoice)> =
+            choices.into_iter().map(|c| (uses(&c), c)).collect();
+        // Stable, so the engine's order breaks ties.
+        ranked.sort_by_key(|(uses, _)| std::cmp::Reverse(*uses));
+        ranked
+            .into_iter()
+            .take(MAX_CONVERSIONS)
+            .map(|(_, choice)| choice.name)
+            .collect()
+    }
+
     /// Runs one line: either a REPL command or numbat code.
     fn run_line(&mut self, line: &str) {
+        if let Some(entry) = self.evaluate(line) {
+            // After evaluating: `restore` replaces the history.
+            let history_line = self.cmd_history.len().checked_sub(1);
+            self.entries.push(HistoryEntry {
+                history_line,
+                ..entry
+            });
+        }
+    }
+
+    /// Evaluates one line into an entry; `clear` and `reset` act on the
+    /// session instead, and so does `restore` besides its entry.
+    fn evaluate(&mut self, line: &str) -> Option<HistoryEntry> {
+        self.names = None;
         let mut parts = line.split_whitespace();
         let command = parts.next().unwrap_or_default();
         let argument = parts.next().unwrap_or_default();
+        let command_output = |markup: Markup| HistoryEntry {
+            printed: vec![markup],
+            ..HistoryEntry::new(line)
+        };
 
-        match (command, argument) {
+        Some(match (command, argument) {
             ("clear", "") => {
                 self.entries.clear();
+                return None;
             }
             ("reset", "") => {
                 self.engine.reset();
                 self.entries.clear();
+                return None;
             }
-            ("list", "") | ("ls", "") => {
-                self.push_command_output(line, self.engine.environment_markup());
-            }
-            ("help", "") | ("?", "") => {
-                self.push_command_output(line, numbat::help::basic_help_markup());
+            ("list", "") | ("ls", "") => command_output(self.engine.environment_markup()),
+            ("help", "") | ("?", "") => command_output(numbat::help::basic_help_markup()),
+            ("info", ident) if !ident.is_empty() => command_output(self.engine.info_markup(ident)),
+            ("checkpoint", _) => {
+                let name = command_argument(line);
+                command_output(self.save_checkpoint(name))
             }
-            ("info", ident) if !ident.is_empty() => {
-                let markup = self.engine.info_markup(ident);
-                self.push_command_output(line, markup);
+            ("restore", _) => match self.restore_checkpoint(command_argument(line)) {
+                Ok(message) => command_output(message),
+                Err(error) => HistoryEntry {
+                    error: Some(error),
+                    ..HistoryEntry::new(line)
+                },
+            },
+            ("sweep", _) => {
+                let sweep =
+                    parse_sweep(line).and_then(|(variable, from, to, steps, expression)| {
+                        self.engine.sweep(variable, from, to, steps, expression)
+                    });
+                let (sweep, error) = match sweep {
+                    Ok(sweep) => (Some(sweep), None),
+                    Err(e) => (None, Some(e)),
+                };
+                HistoryEntry {
+                    sweep,
+                    error,
+                    ..HistoryEntry::new(line)
+                }
             }
             _ => {
                 let output = self.engine.eval(line);
-                self.entries.push(HistoryEntry {
-                    input: line.to_owned(),
+                let conversions = self.rank_conversions(output.conversions);
+                HistoryEntry {
                     printed: output.printed,
                     result: output.result,
                     result_plain: output.result_plain,
+                    conversions,
+                    alternative: output.original,
                     error: output.error,
-                });
+                    ..HistoryEntry::new(line)
+                }
             }
+        })
+    }
+
+    /// Saves the session as a checkpoint named `name` (by default the next
+    /// free number), replacing one of the same name.
+    fn save_checkpoint(&mut self, name: &str) -> Markup {
+        let name = match name {
+            "" => (1..)
+                .map(|n| n.to_string())
+                .find(|n| self.checkpoint(n).is_none())
+                .unwrap_or_default(),
+            name => name.to_owned(),
+        };
+        self.drop_checkpoint(&name);
+        self.checkpoints.push(Checkpoint {
+            name: name.clone(),
+            snapshot: self.snapshot(),
+        });
+        numbat::markup::text("Saved checkpoint ")
+            + numbat::markup::emphasized(CompactString::from(name))
+    }
+
+    /// Rolls the engine, the entries and the command history back to the
+    /// checkpoint named `name` (by default the latest one).
+    fn restore_checkpoint(&mut self, name: &str) -> Result<Markup, String> {
+        let position = match name {
+            "" => self.checkpoints.len().checked_sub(1),
+            name => self.checkpoints.iter().position(|c| c.name == name),
+        };
+        let Some(position) = position else {
+            if self.checkpoints.is_empty() {
+                return Err("No checkpoint saved yet (save one with `checkpoint <name>`)".into());
+            }
+            let names: Vec<&str> = self.checkpoints.iter().map(|c| c.name.as_str()).collect();
+            return Err(format!(
+                "No checkpoint named “{name}” (saved: {})",
+                names.join(", ")
+            ));
+        };
+        let name = CompactString::from(self.checkpoints[position].name.as_str());
+        // The `restore` line stays, so replaying the history rolls back too.
+        let line = self.cmd_history.last().cloned().unwrap_or_default();
+        self.roll_back(self.checkpoints[position].snapshot.clone());
+        self.cmd_history.push(line);
+        Ok(numbat::markup::text("Restored checkpoint ") + numbat::markup::emphasized(name))
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            state: self.engine.snapshot(),
+            entries: self.entries.clone(),
+            cmd_history: self.cmd_history.clone(),
         }
     }
 
-    fn push_command_output(&mut self, input: &str, markup: Markup) {
-        self.entries.push(HistoryEntry {
-            input: input.to_owned(),
-            printed: vec![markup],
-            result: None,
-            result_plain: None,
-            error: None,
+    fn roll_back(&mut self, snapshot: Snapshot) {
+        self.engine.restore(&snapshot.state);
+        self.entries = snapshot.entries;
+        self.cmd_history = snapshot.cmd_history;
+        self.nav_index = None;
+        self.names = None;
+        self.preview_cache = None;
+        self.last_good_preview = None;
+    }
+
+    /// Remembers the session before a clear, reset, deletion or refresh,
+    /// returning the id of the step.
+    fn push_undo(&mut self, notice: &str) -> u64 {
+        if self.undo_stack.len() == MAX_UNDO {
+            self.undo_stack.remove(0);
+        }
+        let id = self.next_undo_id;
+        self.next_undo_id += 1;
+        self.undo_stack.push(UndoStep {
+            id,
+            notice: notice.to_owned(),
+            snapshot: self.snapshot(),
+            engine: None,
         });
+        self.undo_notice = Some((id, notice.to_owned()));
+        id
+    }
+
+    /// Rolls back the latest clear, reset, deletion or refresh, returning
+    /// its id and notice; `None` when there is nothing to undo.
+    pub fn undo(&mut self) -> Option<(u64, String)> {
+        let step = self.undo_stack.pop()?;
+        if let Some(engine) = step.engine {
+            self.engine = engine;
+        }
+        self.roll_back(step.snapshot);
+        self.scroll_to_bottom = true;
+        self.persist_history();
+        Some((step.id, step.notice))
+    }
+
+    /// Undoes the step `id` if it's the latest (undoing an older one would
+    /// take back the later ones too), like `undo`.
+    pub fn undo_step(&mut self, id: u64) -> Option<(u64, String)> {
+        if self.undo_stack.last()?.id != id {
+            return None;
+        }
+        self.undo()
+    }
+
+    /// Whether the step `id` can still be undone.
+    pub fn can_undo(&self, id: u64) -> bool {
+        self.undo_stack.iter().any(|step| step.id == id)
+    }
+
+    /// Drops the undo steps, once new work makes rolling back to them lose it.
+    fn forget_undo(&mut self) {
+        self.undo_stack.clear();
+        self.undo_notice = None;
+    }
+
+    fn checkpoint(&self, name: &str) -> Option<&Checkpoint> {
+        self.checkpoints.iter().find(|c| c.name == name)
+    }
+
+    /// Forgets the checkpoint named `name`.
+    pub fn drop_checkpoint(&mut self, name: &str) {
+        self.checkpoints.retain(|c| c.name != name);
+    }
+
+    /// Evaluates the entry at `index` again as `line`, in its place.
+    fn rerun(&mut self, index: usize, line: &str) {
+        let history_line = self.entries[index].history_line;
+        if let Some(entry) = self.evaluate(line) {
+            self.entries[index] = HistoryEntry {
+                history_line,
+                ..entry
+            };
+        }
+    }
+
+    /// Replaces the input of the entry at `index` with `line` and evaluates
+    /// it in its original position: the engine is rebuilt by replaying the
+    /// command history, which is updated to match. `replay` selects the
+    /// later entries that are re-run (the others keep what they show);
+    /// those whose result changed are flagged.
+    pub fn edit_entry(&mut self, index: usize, line: &str, replay: Replay) {
+        let line = line.trim();
+        // Those act on the whole session, not on an entry.
+        if index >= self.entries.len()
+            || line.is_empty()
+            || acts_on_session(line)
+            || acts_on_session(&self.entries[index].input)
+        {
+            return;
+        }
+        self.forget_undo();
+        let old_input = std::mem::replace(&mut self.entries[index].input, line.to_owned());
+        let Some(history_line) = self.entries[index]
+            .history_line
+            .filter(|i| *i < self.cmd_history.len())
+        else {
+            self.rerun(index, line);
+            return;
+        };
+        self.cmd_history[history_line] = line.to_owned();
+
+        let changed = syntax::dependencies(&old_input)
+            .0
+            .into_iter()
+            .chain(syntax::dependencies(line).0);
+        match replay {
+            Replay::Edited => self.rebuild(Some(index), index + 1, |_, _| false),
+            Replay::Dependents => self.rebuild(Some(index), index + 1, dependents_of(changed)),
+            Replay::All => self.rebuild(Some(index), index + 1, |_, _| true),
+        }
+        self.persist_history();
+    }
+
+    /// Rebuilds the engine from scratch by replaying the command history.
+    /// The `edited` entry is re-run in place, and so are the entries from
+    /// `first` on that `rerun` picks (given their input and whether the
+    /// entry before was re-run); the rest only contribute their definitions.
+    fn rebuild(
+        &mut self,
+        edited: Option<usize>,
+        first: usize,
+        mut rerun: impl FnMut(&str, bool) -> bool,
+    ) {
+        for entry in &mut self.entries {
+            entry.recomputed = false;
+        }
+        let entry_at: HashMap<usize, usize> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| Some((entry.history_line?, index)))
+            .collect();
+        let outcome = |entry: &HistoryEntry| (entry.copy_text(), entry.error.clone());
+
+        self.engine.reset();
+        let mut previous_rerun = false;
+        for history_line in 0..self.cmd_history.len() {
+            let line = self.cmd_history[history_line].clone();
+            let index = entry_at.get(&history_line).copied();
+            previous_rerun = match (index, edited) {
+                (Some(index), Some(edited)) if index == edited => {
+                    self.rerun(index, &line);
+                    true
+                }
+                (Some(index), _)
+                    if index >= first
+                        && !acts_on_session(&line)
+                        && rerun(&line, previous_rerun) =>
+                {
+                    let before = outcome(&self.entries[index]);
+                    self.rerun(index, &line);
+                    self.entries[index].recomputed = outcome(&self.entries[index]) != before;
+                    true
+                }
+                _ => {
+                    if line.trim() == "reset" {
+                        self.engine.reset();
+                    } else if !is_repl_command(&line) {
+                        self.engine.replay(&line);
+                    }
+                    false
+                }
+            };
+        }
+        self.names = None;
+        self.preview_cache = None;
+        self.last_good_preview = None;
     }
 
     pub fn clear(&mut self) {
+        if !self.entries.is_empty() {
+            self.push_undo("History cleared");
+        }
         self.entries.clear();
         self.input.clear();
         self.nav_index = None;
@@ -138,10 +601,35 @@ impl Session {
     /// Deletes a single entry. The engine state (definitions) is unaffected.
     pub fn delete_entry(&mut self, index: usize) {
         if index < self.entries.len() {
+            self.push_undo("Entry removed");
             self.entries.remove(index);
         }
     }
 
+    /// Deletes an entry together with its line in the command history, and
+    /// rebuilds the engine without it, so that what it defined is gone
+    /// (also after a restart). Later entries using those names are re-run,
+    /// to show that they no longer work.
+    pub fn retract_entry(&mut self, index: usize) {
+        if index >= self.entries.len() {
+            return;
+        }
+        self.push_undo("Entry removed and retracted");
+        let entry = self.entries.remove(index);
+        if let Some(removed) = entry.history_line.filter(|i| *i < self.cmd_history.len()) {
+            self.cmd_history.remove(removed);
+            for later in &mut self.entries {
+                if let Some(line) = later.history_line.as_mut().filter(|line| **line > removed) {
+                    *line -= 1;
+                }
+            }
+        }
+        self.nav_index = None;
+        let retracted = syntax::dependencies(&entry.input).0;
+        self.rebuild(None, index, dependents_of(retracted));
+        self.persist_history();
+    }
+
     /// The most recent copyable result, if any.
     pub fn last_result_plain(&self) -> Option<&str> {
         self.entries
@@ -150,6 +638,103 @@ impl Session {
             .find_map(|e| e.result_plain.as_deref())
     }
 
+    /// The names currently defined in the engine (units, functions,
+    /// variables, dimensions).
+    pub fn names(&mut self) -> Arc<Names> {
+        let engine = &self.engine;
+        Arc::clone(self.names.get_or_insert_with(|| Arc::new(engine.names())))
+    }
+
+    /// The variables, functions, units and dimensions defined since the
+    /// last `reset`, in the order they were first defined.
+    pub fn definitions(&mut self) -> Arc<Vec<Definition>> {
+        let names = self.names();
+        if let Some((built_for, definitions)) = &self.definitions {
+            if Arc::ptr_eq(built_for, &names) {
+                return Arc::clone(definitions);
+            }
+        }
+        let mut defined: Vec<(&str, usize)> = Vec::new();
+        for line in self.session_lines() {
+            for name in syntax::dependencies(&self.cmd_history[line]).0 {
+                match defined.iter_mut().find(|(n, _)| *n == name) {
+                    Some(definition) => definition.1 = line,
+                    None => defined.push((name, line)),
+                }
+            }
+        }
+        let definitions: Vec<Definition> = defined
+            .into_iter()
+            .filter_map(|(name, line)| {
+                let kind = names.classify(name);
+                let line = &self.cmd_history[line];
+                let (detail, quantity) = match kind {
+                    NameKind::Variable => {
+                        let (value, quantity) = self.engine.variable(name)?;
+                        (value, quantity.filter(|_| let_prefix(line, name).is_some()))
+                    }
+                    NameKind::Function => (self.engine.signature(name)?, None),
+                    NameKind::Unit => (self.engine.dimension_of(name).ok()?, None),
+                    NameKind::Dimension => {
+                        let definition = line.split_once('=').map(|(_, d)| d.trim());
+                        (definition.unwrap_or("base dimension").to_owned(), None)
+                    }
+                    NameKind::Unknown => return None,
+                };
+                Some(Definition {
+                    name: name.to_owned(),
+                    kind,
+                    detail,
+                    quantity,
+                })
+            })
+            .collect();
+        let definitions = Arc::new(definitions);
+        self.definitions = Some((names, Arc::clone(&definitions)));
+        definitions
+    }
+
+    /// The indices of the command history lines since the last `reset`,
+    /// skipping REPL commands.
+    fn session_lines(&self) -> impl Iterator<Item = usize> + '_ {
+        let start = self
+            .cmd_history
+            .iter()
+            .rposition(|line| line.trim() == "reset")
+            .map_or(0, |reset| reset + 1);
+        (start..self.cmd_history.len()).filter(|&i| !is_repl_command(&self.cmd_history[i]))
+    }
+
+    /// Gives the variable `name` a new value (like `2.5 m`) by editing the
+    /// plain `let` that defines it in place, re-running what depends on it.
+    pub fn redefine(&mut self, name: &str, value: &str) {
+        let Some(line) = self
+            .session_lines()
+            .filter(|&i| syntax::dependencies(&self.cmd_history[i]).0.contains(&name))
+            .last()
+        else {
+            return;
+        };
+        let Some(prefix) = let_prefix(&self.cmd_history[line], name) else {
+            return;
+        };
+        let input = format!("{prefix} = {value}");
+        match self
+            .entries
+            .iter()
+            .position(|e| e.history_line == Some(line))
+        {
+            Some(index) => self.edit_entry(index, &input, Replay::Dependents),
+            // Cleared from view: only the history changes.
+            None => {
+                self.forget_undo();
+                self.cmd_history[line] = input;
+                self.rebuild(None, 0, dependents_of([name]));
+                self.persist_history();
+            }
+        }
+    }
+
     // ---- Command history navigation -------------------------------------
 
     pub fn navigate_up(&mut self) -> bool {
@@ -183,6 +768,11 @@ impl Session {
         true
     }
 
+    /// Whether Up/Down currently walk through the command history.
+    pub fn is_navigating_history(&self) -> bool {
+        self.nav_index.is_some()
+    }
+
     /// Must be called whenever the user edits the input, so that history
     /// navigation restarts from the new text.
     pub fn on_input_edited(&mut self) {
@@ -210,7 +800,12 @@ impl Session {
             .as_ref()
             .is_none_or(|(input, _)| input != &self.input);
         if outdated {
-            let result = self.engine.preview(&self.input);
+            let result = self.engine.preview(&self.input).map(|value| Preview {
+                markup: value.markup,
+                plain: value.plain,
+                conversions: self.rank_conversions(value.conversions),
+                fresh: true,
+            });
             if let Some(good) = &result {
                 self.last_good_preview = Some(good.clone());
             }
@@ -220,22 +815,37 @@ impl Session {
         let current = self
             .preview_cache
             .as_ref()
-            .and_then(|(_, result)| result.as_ref());
-        match current {
-            Some((markup, plain)) => Some(Preview {
-                markup: markup.clone(),
-                plain: plain.clone(),
-                fresh: true,
-            }),
-            None => self
-                .last_good_preview
-                .as_ref()
-                .map(|(markup, plain)| Preview {
-                    markup: markup.clone(),
-                    plain: plain.clone(),
-                    fresh: false,
-                }),
+            .and_then(|(_, result)| result.clone());
+        current.or_else(|| {
+            self.last_good_preview.clone().map(|preview| Preview {
+                fresh: false,
+                ..preview
+            })
+        })
+    }
+
+    /// The session as a numbat script: every evaluated line, with its
+    /// result (or error) as a comment, so the file runs in the numbat CLI.
+    /// REPL commands and lines that failed are commented out.
+    pub fn export_script(&self) -> String {
+        let mut script = String::new();
+        for entry in &self.entries {
+            if is_repl_command(&entry.input) || entry.error.is_some() {
+                script.push_str("# ");
+            }
+            script.push_str(&entry.input);
+            if let Some(result) = &entry.result_plain {
+                script.push_str(&format!("  # = {result}"));
+            }
+            script.push('\n');
+            if let Some(error) = &entry.error {
+                let message = error.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+                let message = message.trim();
+                let message = message.strip_prefix("error: ").unwrap_or(message);
+                script.push_str(&format!("# error: {message}\n"));
+            }
         }
+        script
     }
 
     // ---- Persistence -----------------------------------------------------
@@ -269,6 +879,25 @@ impl Session {
         }
     }
 
+    /// Replays the persisted history with `engine`, as after a restart —
+    /// so that all results are shown with new formatting options. Undoing
+    /// it brings back the old engine and entries; returns the step's id.
+    pub fn refresh(&mut self, engine: Engine) -> u64 {
+        let id = self.push_undo("Results reformatted");
+        let previous = std::mem::replace(&mut self.engine, engine);
+        if let Some(step) = self.undo_stack.last_mut() {
+            step.engine = Some(previous);
+        }
+        self.entries.clear();
+        self.cmd_history.clear();
+        self.conversion_counts.clear();
+        self.restore_history();
+        self.names = None;
+        self.preview_cache = None;
+        self.last_good_preview = None;
+        id
+    }
+
     /// Replays persisted input lines to rebuild both the visible history and
     /// the engine state (variable/function definitions).
     pub fn restore_history(&mut self) {
@@ -277,7 +906,7 @@ impl Session {
             if line.trim().is_empty() {
                 continue;
             }
-            self.cmd_history.push(line.clone());
+            self.push_history(line.clone());
             self.run_line(&line);
         }
         self.scroll_to_bottom = true;
@@ -293,14 +922,121 @@ impl Session {
     }
 }
 
-fn is_repl_command(line: &str) -> bool {
+/// Picks the entries to re-run after the definitions of `changed` names
+/// changed: those using a changed name, and those using `ans` right after a
+/// re-run entry. The names a re-run entry defines change in turn; a
+/// redefinition that doesn't depend on the change hides it.
+fn dependents_of<'a>(changed: impl IntoIterator<Item = &'a str>) -> impl FnMut(&str, bool) -> bool {
+    let mut changed: HashSet<String> = changed.into_iter().map(str::to_owned).collect();
+    move |input: &str, previous_rerun: bool| {
+        let (defines, uses) = syntax::dependencies(input);
+        let depends = uses.iter().any(|name| changed.contains(*name))
+            || (previous_rerun && uses.iter().any(|name| matches!(*name, "ans" | "_")));
+        for name in defines {
+            if depends {
+                changed.insert(name.to_owned());
+            } else {
+                changed.remove(name);
+            }
+        }
+        depends
+    }
+}
+
+/// Splits `3 m -> ft` into the expression and the unit it converts to.
+pub fn split_conversion(line: &str) -> Option<(&str, &str)> {
+    let (start, end) = CONVERSION_OPERATORS
+        .iter()
+        .filter_map(|op| line.rfind(op).map(|i| (i, i + op.len())))
+        .max_by_key(|(start, _)| *start)?;
+    let unit = line[end..].trim();
+    let is_unit = !unit.is_empty()
+        && unit
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '°'));
+    is_unit.then(|| (line[..start].trim_end(), unit))
+}
+
+/// `line` converted to `unit`, replacing a conversion it already ends in.
+pub fn with_conversion(line: &str, unit: &str) -> String {
+    let expression = split_conversion(line).map_or(line.trim(), |(expression, _)| expression);
+    format!("{expression} -> {unit}")
+}
+
+/// Whether `line` is a REPL command (`list`, `sweep`, …) rather than
+/// numbat code.
+pub fn is_repl_command(line: &str) -> bool {
     let first = line.split_whitespace().next().unwrap_or_default();
     matches!(
         first,
-        "clear" | "reset" | "list" | "ls" | "help" | "?" | "info"
+        "clear"
+            | "reset"
+            | "list"
+            | "ls"
+            | "help"
+            | "?"
+            | "info"
+            | "sweep"
+            | "checkpoint"
+            | "restore"
     )
 }
 
+/// `let x` or `let x: Length` when `line` is nothing but a definition of
+/// the variable `name`, for giving it another value.
+fn let_prefix<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let (prefix, value) = line.trim().split_once('=')?;
+    let declared = prefix.strip_prefix("let")?.split(':').next()?.trim();
+    let plain = declared == name && !value.contains([';', '\n']) && !value.starts_with('=');
+    plain.then_some(prefix.trim_end())
+}
+
+/// Whether `line` is a REPL command that changes the whole session, which
+/// can't be evaluated again in the place of an entry.
+fn acts_on_session(line: &str) -> bool {
+    let line = line.trim();
+    let first = line.split_whitespace().next().unwrap_or_default();
+    matches!(line, "clear" | "reset") || matches!(first, "checkpoint" | "restore")
+}
+
+/// What follows the command word of a REPL command, like the name in
+/// `checkpoint before tuning`.
+fn command_argument(line: &str) -> &str {
+    let line = line.trim();
+    line.split_once(char::is_whitespace)
+        .map_or("", |(_, rest)| rest.trim())
+}
+
+/// Splits `sweep v from 10 m/s to 50 m/s in 5 steps: 0.5 m v^2` into the
+/// variable, the range, the number of steps and the expression.
+pub fn parse_sweep(line: &str) -> Result<(&str, &str, &str, usize, &str), String> {
+    let usage = || SWEEP_USAGE.to_owned();
+    let rest = line.trim().strip_prefix("sweep").ok_or_else(usage)?;
+    let (head, expression) = rest.split_once("steps:").ok_or_else(usage)?;
+    let (variable, range) = head.split_once(" from ").ok_or_else(usage)?;
+    let (from, rest) = range.split_once(" to ").ok_or_else(usage)?;
+    // The last " in ": the range may well be in inches.
+    let (to, steps) = rest.rsplit_once(" in ").ok_or_else(usage)?;
+    let variable = variable.trim();
+    let is_identifier = variable
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && variable.chars().all(|c| c.is_alphanumeric() || c == '_');
+    let steps: usize = steps.trim().parse().map_err(|_| usage())?;
+    if !is_identifier
+        || from.trim().is_empty()
+        || to.trim().is_empty()
+        || expression.trim().is_empty()
+    {
+        return Err(usage());
+    }
+    if !(2..=MAX_SWEEP_STEPS).contains(&steps) {
+        return Err(format!("A sweep takes 2 to {MAX_SWEEP_STEPS} steps"));
+    }
+    Ok((variable, from.trim(), to.trim(), steps, expression.trim()))
+}
+
 /// Best-effort migration from the storage of numbat-ui 2.x, which kept the
 /// command history in eframe's `app.ron` (a RON map with an "app" key
 /// holding a RON-encoded `Vec<String>`).
@@ -316,12 +1052,17 @@ fn legacy_history_lines() -> Option<Vec<String>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine::PreferredUnits;
     use numbat::FormatOptions;
 
     fn session() -> Session {
         Session::new(Engine::new(FormatOptions::default()))
     }
 
+    fn undo(s: &mut Session) -> Option<String> {
+        s.undo().map(|(_, notice)| notice)
+    }
+
     #[test]
     fn submit_appends_entry() {
         let mut s = session();
@@ -386,6 +1127,350 @@ mod tests {
         assert_eq!(s.last_result_plain(), Some("42"));
     }
 
+    #[test]
+    fn copies_results_or_command_output() {
+        let mut s = session();
+        s.input = "let x = 6 * 7".to_owned();
+        s.submit();
+        s.input = "x".to_owned();
+        s.submit();
+        s.input = "list".to_owned();
+        s.submit();
+        assert_eq!(s.entries[1].copy_text().as_deref(), Some("42"));
+        assert!(s.entries[2].copy_text().is_some_and(|t| t.contains('x')));
+    }
+
+    #[test]
+    fn export_script_runs_in_the_cli() {
+        let mut s = session();
+        for line in ["let x = 6", "x * 7", "list", "1 +"] {
+            s.input = line.to_owned();
+            s.submit();
+        }
+        let script = s.export_script();
+        let lines: Vec<&str> = script.lines().collect();
+        assert_eq!(lines[0], "let x = 6");
+        assert_eq!(lines[1], "x * 7  # = 42");
+        assert_eq!(lines[2], "# list");
+        assert_eq!(lines[3], "# 1 +");
+        assert!(lines[4].starts_with("# error: "));
+        assert!(!lines[4].starts_with("# error: error"));
+
+        let mut cli = Engine::new(FormatOptions::default());
+        let output = cli.eval(&script);
+        assert_eq!(output.error, None);
+        assert_eq!(output.result_plain.as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn ranks_conversions_by_earlier_use() {
+        let mut s = session();
+        s.input = "4.2 km".to_owned();
+        s.submit();
+        assert_eq!(s.entries[0].conversions, ["m", "mi", "ft", "nmi"]);
+        s.input = "1 m -> inches".to_owned();
+        s.submit();
+        s.input = "4.2 km".to_owned();
+        s.submit();
+        assert_eq!(s.entries[2].conversions, ["in", "m", "mi", "ft"]);
+    }
+
+    #[test]
+    fn reverts_to_the_computed_unit() {
+        let mut s = Session::new(
+            Engine::new(FormatOptions::default()).with_preferred_units(PreferredUnits::Imperial),
+        );
+        s.input = "4.2 km".to_owned();
+        s.submit();
+        let entry = &mut s.entries[0];
+        assert_eq!(entry.result_plain.as_deref(), Some("2.60976 mi"));
+        entry.toggle_preferred_units();
+        assert!(entry.reverted);
+        assert_eq!(entry.result_plain.as_deref(), Some("4.2 km"));
+        assert_eq!(s.last_result_plain(), Some("4.2 km"));
+        s.entries[0].toggle_preferred_units();
+        assert_eq!(s.entries[0].result_plain.as_deref(), Some("2.60976 mi"));
+    }
+
+    #[test]
+    fn conversions_replace_the_target_unit() {
+        assert_eq!(split_conversion("3 m -> ft"), Some(("3 m", "ft")));
+        assert_eq!(split_conversion("3 m to ft"), Some(("3 m", "ft")));
+        assert_eq!(split_conversion("20 °C → °F"), Some(("20 °C", "°F")));
+        assert_eq!(split_conversion("3 m -> ft + 1"), None);
+        assert_eq!(split_conversion("3 m"), None);
+        assert_eq!(with_conversion("4.2 km", "mi"), "4.2 km -> mi");
+        assert_eq!(with_conversion("4.2 km -> mi", "ft"), "4.2 km -> ft");
+    }
+
+    #[test]
+    fn sweeps_into_a_table() {
+        assert_eq!(
+            parse_sweep("sweep d from 1 in to 2 in in 3 steps: d -> cm"),
+            Ok(("d", "1 in", "2 in", 3, "d -> cm"))
+        );
+        assert!(parse_sweep("sweep 2x from 1 to 2 in 3 steps: x").is_err());
+        assert!(parse_sweep("sweep x from 1 to 2 in 1 steps: x").is_err());
+
+        let mut s = session();
+        s.input = "sweep v from 10 m/s to 50 m/s in 5 steps: v * 2 s".to_owned();
+        s.submit();
+        let sweep = s.entries[0].sweep.as_ref().unwrap();
+        assert_eq!(sweep.rows.len(), 5);
+        assert_eq!(sweep.rows[0].1.as_deref(), Ok("20 m"));
+        assert!(s.entries[0].copy_text().unwrap().starts_with("v,v * 2 s\n"));
+        assert!(s.export_script().starts_with("# sweep"));
+
+        s.input = "sweep v from 1 to 2: v".to_owned();
+        s.submit();
+        assert!(s.entries[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("usage: sweep"));
+    }
+
+    #[test]
+    fn recomputes_entries_depending_on_an_edited_definition() {
+        let mut s = session();
+        for line in [
+            "let mass = 1200 kg",
+            "let weight = mass * gravity -> N",
+            "weight / 2",
+            "ans * 2",
+            "3 m",
+            "let mass = 5 kg",
+            "mass * 2",
+        ] {
+            s.input = line.to_owned();
+            s.submit();
+        }
+        s.edit_entry(0, "let mass = 1000 kg", Replay::Dependents);
+        let results: Vec<Option<&str>> = s
+            .entries
+            .iter()
+            .map(|e| e.result_plain.as_deref())
+            .collect();
+        assert_eq!(
+            results[1..],
+            [
+                None,
+                Some("4903.33 N"),
+                Some("9806.65 N"),
+                Some("3 m"),
+                None,
+                Some("10 kg"),
+            ]
+        );
+        let recomputed: Vec<bool> = s.entries.iter().map(|e| e.recomputed).collect();
+        assert_eq!(recomputed, [false, false, true, true, false, false, false]);
+        assert_eq!(s.cmd_history[0], "let mass = 1000 kg");
+
+        // Without recomputation, only the edited entry changes.
+        s.edit_entry(0, "let mass = 2000 kg", Replay::Edited);
+        assert_eq!(s.entries[2].result_plain.as_deref(), Some("4903.33 N"));
+        assert!(s.entries.iter().all(|e| !e.recomputed));
+    }
+
+    #[test]
+    fn edits_evaluate_in_their_original_position() {
+        let mut s = session();
+        for line in ["let x = 2", "x * 10", "let x = 3", "x * 10"] {
+            s.input = line.to_owned();
+            s.submit();
+        }
+        // Sees the first x, not the one defined after it.
+        s.edit_entry(1, "x * 100", Replay::Edited);
+        assert_eq!(s.entries[1].result_plain.as_deref(), Some("200"));
+        assert_eq!(s.entries[3].result_plain.as_deref(), Some("30"));
+
+        s.edit_entry(2, "let x = 4", Replay::Edited);
+        assert_eq!(s.entries[3].result_plain.as_deref(), Some("30"));
+        s.edit_entry(2, "let x = 5", Replay::All);
+        assert_eq!(s.entries[3].result_plain.as_deref(), Some("50"));
+        assert!(s.entries[3].recomputed);
+        assert!(!s.entries[1].recomputed);
+        s.input = "x".to_owned();
+        s.submit();
+        assert_eq!(s.entries[4].result_plain.as_deref(), Some("5"));
+        assert_eq!(
+            s.cmd_history,
+            ["let x = 2", "x * 100", "let x = 5", "x * 10", "x"]
+        );
+    }
+
+    #[test]
+    fn retracting_an_entry_forgets_its_definitions() {
+        let mut s = session();
+        for line in ["let a = 2", "let b = 3", "a * b", "b * 2"] {
+            s.input = line.to_owned();
+            s.submit();
+        }
+        s.retract_entry(0);
+        assert_eq!(s.cmd_history, ["let b = 3", "a * b", "b * 2"]);
+        assert!(s.entries[1].error.is_some());
+        assert!(s.entries[1].recomputed);
+        assert_eq!(s.entries[2].result_plain.as_deref(), Some("6"));
+        assert!(!s.entries[2].recomputed);
+        assert_eq!(s.entries[2].history_line, Some(2));
+        s.input = "a".to_owned();
+        s.submit();
+        assert!(s.entries[3].error.is_some());
+
+        // Plain deletion keeps the definitions.
+        s.delete_entry(0);
+        s.input = "b".to_owned();
+        s.submit();
+        assert_eq!(s.entries[3].result_plain.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn restores_checkpoints() {
+        let mut s = session();
+        for line in [
+            "let a = 2",
+            "checkpoint",
+            "let a = 3",
+            "let b = a",
+            "restore 1",
+        ] {
+            s.input = line.to_owned();
+            s.submit();
+        }
+        let inputs: Vec<&str> = s.entries.iter().map(|e| e.input.as_str()).collect();
+        assert_eq!(inputs, ["let a = 2", "restore 1"]);
+        assert_eq!(s.cmd_history, ["let a = 2", "checkpoint", "restore 1"]);
+        assert_eq!(s.entries[1].history_line, Some(2));
+        for line in ["a", "b", "restore nope"] {
+            s.input = line.to_owned();
+            s.submit();
+        }
+        assert_eq!(s.entries[2].result_plain.as_deref(), Some("2"));
+        assert!(s.entries[3].error.is_some());
+        assert!(s.entries[4].error.as_ref().unwrap().contains("saved: 1"));
+
+        // Checkpoints outlive `reset`, and a later one of the same name
+        // replaces the earlier.
+        for line in [
+            "checkpoint two",
+            "let a = 5",
+            "checkpoint two",
+            "reset",
+            "restore two",
+            "a",
+        ] {
+            s.input = line.to_owned();
+            s.submit();
+        }
+        assert_eq!(s.checkpoints.len(), 2);
+        assert_eq!(s.last_result_plain(), Some("5"));
+    }
+
+    #[test]
+    fn undoes_clears_resets_and_deletions() {
+        let mut s = session();
+        for line in ["let a = 2", "a + 1", "reset"] {
+            s.input = line.to_owned();
+            s.submit();
+        }
+        assert_eq!(
+            s.undo_notice.take().map(|(_, notice)| notice).as_deref(),
+            Some("Session reset")
+        );
+        assert!(s.entries.is_empty());
+        assert_eq!(undo(&mut s).as_deref(), Some("Session reset"));
+        assert_eq!(s.cmd_history, ["let a = 2", "a + 1"]);
+
+        s.delete_entry(1);
+        s.clear();
+        assert!(s.entries.is_empty());
+        assert_eq!(undo(&mut s).as_deref(), Some("History cleared"));
+        assert_eq!(s.entries.len(), 1);
+        assert_eq!(undo(&mut s).as_deref(), Some("Entry removed"));
+        assert_eq!(s.entries.len(), 2);
+        assert_eq!(s.undo(), None);
+
+        // A toast only undoes its own step, while it's the latest.
+        s.delete_entry(0);
+        let (first, _) = s.undo_notice.take().unwrap();
+        s.delete_entry(0);
+        assert_eq!(s.undo_step(first), None);
+        assert!(s.undo_step(first + 1).is_some());
+        assert_eq!(s.entries.len(), 1);
+        assert!(s.undo_step(first).is_some());
+        assert!(!s.can_undo(first));
+
+        // New work can't be rolled back by undoing an earlier step.
+        s.delete_entry(0);
+        s.input = "a * 3".to_owned();
+        s.submit();
+        assert_eq!(s.undo(), None);
+        assert_eq!(s.entries.len(), 2);
+
+        // Definitions come back too.
+        s.input = "a".to_owned();
+        s.submit();
+        assert_eq!(s.last_result_plain(), Some("2"));
+
+        // A refresh brings back the old engine, and keeps earlier steps.
+        s.clear();
+        let id = s.refresh(
+            Engine::new(FormatOptions::default()).with_preferred_units(PreferredUnits::Si),
+        );
+        assert!(s.can_undo(id));
+        assert_eq!(undo(&mut s).as_deref(), Some("Results reformatted"));
+        assert_eq!(s.engine.preferred_units, PreferredUnits::AsComputed);
+        assert!(s.entries.is_empty());
+        assert_eq!(undo(&mut s).as_deref(), Some("History cleared"));
+        assert_eq!(s.last_result_plain(), Some("2"));
+    }
+
+    #[test]
+    fn inspects_and_redefines_definitions() {
+        let mut s = session();
+        for line in [
+            "let r = 2 m",
+            "fn area(radius: Length) -> Area = pi radius^2",
+            "unit furlong = 201.168 m",
+            "let ratio = r / 1 furlong",
+            "area(r)",
+            "let r = 3 m",
+        ] {
+            s.input = line.to_owned();
+            s.submit();
+        }
+        let definitions = s.definitions();
+        let names: Vec<(&str, NameKind)> = definitions
+            .iter()
+            .map(|d| (d.name.as_str(), d.kind))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("r", NameKind::Variable),
+                ("area", NameKind::Function),
+                ("furlong", NameKind::Unit),
+                ("ratio", NameKind::Variable),
+            ]
+        );
+        assert_eq!(definitions[0].quantity, Some((3.0, "m".to_owned())));
+        assert!(definitions[1].detail.contains("Length"));
+        assert_eq!(definitions[2].detail, "Length");
+
+        // The latest definition changes; what depends on it re-runs.
+        s.redefine("r", "4 m");
+        assert_eq!(s.entries[5].input, "let r = 4 m");
+        assert_eq!(s.entries[4].result_plain.as_deref(), Some("12.5664 m²"));
+        assert_eq!(s.definitions()[0].quantity, Some((4.0, "m".to_owned())));
+
+        assert_eq!(
+            let_prefix("let x: Length = 2 m", "x"),
+            Some("let x: Length")
+        );
+        assert_eq!(let_prefix("let x = 2; let y = 3", "x"), None);
+        assert_eq!(let_prefix("let xs = 2", "x"), None);
+    }
+
     #[test]
     fn preview_is_cached_per_input() {
         let mut s = session();
@@ -0,0 +1,803 @@
+//! Lexing of input lines for syntax highlighting, and a snapshot of the
+//! names defined in the current environment.
+//!
+//! numbat's own tokenizer is private to the crate, so this module follows
+//! its rules (number literals, identifier characters, string interpolation,
+//! scopes) as closely as possible. Unlike numbat's, it never fails: input
+//! is highlighted while it is being typed and is incomplete most of the
+//! time, so problems are reported as `Invalid` tokens instead of errors.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Whitespace,
+    Comment,
+    /// Decimal, scientific (`1e-3`) or base-prefixed (`0x1F`) literal.
+    Number,
+    Identifier,
+    /// A string literal, or the literal part of an interpolated string
+    /// (including the quotes and the `{`/`}` around interpolations).
+    String,
+    Operator,
+    /// `,`, `;`, `:`, `::` and `.`.
+    Punctuation,
+    /// `(`, `[` or a `{` that opens a struct.
+    OpenBracket,
+    /// `)`, `]` or a `}` that closes a struct.
+    CloseBracket,
+    /// A character numbat would reject.
+    Invalid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Byte range in the input.
+    pub range: Range<usize>,
+}
+
+/// Words the tokenizer turns into keyword tokens (procedures included).
+const KEYWORDS: &[&str] = &[
+    "per",
+    "to",
+    "let",
+    "fn",
+    "where",
+    "and",
+    "dimension",
+    "unit",
+    "use",
+    "struct",
+    "long",
+    "short",
+    "both",
+    "none",
+    "if",
+    "then",
+    "else",
+    "true",
+    "false",
+    "NaN",
+    "inf",
+    "print",
+    "assert",
+    "assert_eq",
+    "type",
+];
+
+/// Built-in type names.
+const TYPE_NAMES: &[&str] = &["Bool", "String", "DateTime", "Fn", "List"];
+
+/// Temperature conversion functions that are used like units (`20 °C`).
+const TEMPERATURE_PSEUDO_UNITS: &[&str] = &[
+    "celsius",
+    "degree_celsius",
+    "fahrenheit",
+    "degree_fahrenheit",
+    "°C",
+    "°F",
+];
+
+/// Identifiers that refer to the previous result.
+const RESERVED_VARIABLES: &[&str] = &["ans", "_"];
+
+pub fn is_keyword(word: &str) -> bool {
+    KEYWORDS.contains(&word)
+}
+
+pub fn is_type_name(word: &str) -> bool {
+    TYPE_NAMES.contains(&word)
+}
+
+fn is_exponent_char(c: char) -> bool {
+    matches!(c, '¹' | '²' | '³' | '⁴' | '⁵' | '⁶' | '⁷' | '⁸' | '⁹')
+}
+
+fn is_numerical_fraction_char(c: char) -> bool {
+    matches!(
+        c,
+        '¼' | '½'
+            | '¾'
+            | '⅐'
+            | '⅑'
+            | '⅒'
+            | '⅓'
+            | '⅔'
+            | '⅕'
+            | '⅖'
+            | '⅗'
+            | '⅘'
+            | '⅙'
+            | '⅚'
+            | '⅛'
+            | '⅜'
+            | '⅝'
+            | '⅞'
+    )
+}
+
+fn is_currency_char(c: char) -> bool {
+    ('\u{20A0}'..='\u{20CF}').contains(&c) || matches!(c, '£' | '¥' | '$' | '฿')
+}
+
+fn is_subscript_char(c: char) -> bool {
+    ('\u{2080}'..='\u{209C}').contains(&c)
+}
+
+pub fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic()
+        || is_numerical_fraction_char(c)
+        || is_currency_char(c)
+        || matches!(c, '%' | '‰' | '°' | '′' | '″' | '_')
+}
+
+pub fn is_identifier_continue(c: char) -> bool {
+    (c.is_alphanumeric()
+        || c == '_'
+        || is_subscript_char(c)
+        || is_currency_char(c)
+        || matches!(c, '%' | '‰'))
+        && !is_exponent_char(c)
+        && c != '⁰'
+        && c != '·'
+        && c != '⋅'
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Curly,
+    String,
+}
+
+struct Lexer<'a> {
+    text: &'a str,
+    pos: usize,
+    scopes: Vec<Scope>,
+    tokens: Vec<Token>,
+}
+
+impl Lexer<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn peek2(&self) -> Option<char> {
+        let mut chars = self.text[self.pos..].chars();
+        chars.next();
+        chars.next()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_while(&mut self, pred: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&pred) {
+            self.advance();
+        }
+    }
+
+    fn is_inside_interpolation(&self) -> bool {
+        let n = self.scopes.len();
+        n >= 2 && self.scopes[n - 1] == Scope::Curly && self.scopes[n - 2] == Scope::String
+    }
+
+    /// Consumes string contents up to (not including) the closing quote or
+    /// the brace of an interpolation. `{{` and `}}` are escaped braces.
+    fn consume_string(&mut self) {
+        let mut escaped = false;
+        while let Some(c) = self.peek() {
+            match c {
+                '\\' if !escaped => {
+                    escaped = true;
+                    self.advance();
+                    continue;
+                }
+                '"' if !escaped => break,
+                '{' | '}' if self.peek2() != Some(c) => break,
+                '{' | '}' => {
+                    self.advance();
+                }
+                _ => {}
+            }
+            escaped = false;
+            self.advance();
+        }
+    }
+
+    /// After the literal part of a string: either its end, or the start of
+    /// an interpolation. Unterminated strings simply run to the end.
+    fn finish_string_part(&mut self) {
+        if self.eat('"') {
+            self.scopes.pop();
+        } else if self.eat('{') {
+            self.scopes.push(Scope::Curly);
+        }
+    }
+
+    fn scientific_notation(&mut self) {
+        if matches!(self.peek(), Some('e' | 'E'))
+            && self
+                .peek2()
+                .is_some_and(|c| c.is_ascii_digit() || c == '+' || c == '-')
+        {
+            self.advance();
+            let _ = self.eat('+') || self.eat('-');
+            self.eat_while(|c| c.is_ascii_digit() || c == '_');
+        }
+    }
+
+    fn next_token(&mut self) -> TokenKind {
+        let c = self.advance().expect("not at end");
+
+        match c {
+            ' ' | '\t' | '\r' | '\n' => {
+                self.eat_while(|c| matches!(c, ' ' | '\t' | '\r' | '\n'));
+                TokenKind::Whitespace
+            }
+            '#' => {
+                self.eat_while(|c| c != '\n');
+                TokenKind::Comment
+            }
+            '(' | '[' => TokenKind::OpenBracket,
+            ')' | ']' => TokenKind::CloseBracket,
+            '"' => {
+                self.scopes.push(Scope::String);
+                self.consume_string();
+                self.finish_string_part();
+                TokenKind::String
+            }
+            '}' if self.is_inside_interpolation() => {
+                self.scopes.pop();
+                self.consume_string();
+                self.finish_string_part();
+                TokenKind::String
+            }
+            ':' if self.is_inside_interpolation() => {
+                // Format specifiers, e.g. `{x:.3}`.
+                self.eat_while(|c| c != '"' && c != '}');
+                TokenKind::String
+            }
+            '{' if self.is_inside_interpolation() => TokenKind::Invalid,
+            '{' => {
+                self.scopes.push(Scope::Curly);
+                TokenKind::OpenBracket
+            }
+            '}' => {
+                if self.scopes.last() == Some(&Scope::Curly) {
+                    self.scopes.pop();
+                }
+                TokenKind::CloseBracket
+            }
+            '0' if matches!(self.peek(), Some('x' | 'o' | 'b'))
+                && self.peek2().is_some_and(|d| d.is_ascii_hexdigit()) =>
+            {
+                let base = match self.advance() {
+                    Some('x') => 16,
+                    Some('o') => 8,
+                    _ => 2,
+                };
+                self.eat_while(|c| c.is_digit(base) || c == '_');
+                TokenKind::Number
+            }
+            c if c.is_ascii_digit() => {
+                self.eat_while(|c| c.is_ascii_digit() || c == '_');
+                if self.peek() == Some('.') && self.peek2() != Some('.') {
+                    self.advance();
+                    self.eat_while(|c| c.is_ascii_digit() || c == '_');
+                }
+                self.scientific_notation();
+                TokenKind::Number
+            }
+            '.' if self.peek() == Some('.') && self.peek2() == Some('.') => {
+                self.advance();
+                self.advance();
+                TokenKind::Operator
+            }
+            '.' if self.peek().is_some_and(|c| c.is_ascii_digit()) => {
+                self.eat_while(|c| c.is_ascii_digit() || c == '_');
+                self.scientific_notation();
+                TokenKind::Number
+            }
+            '.' => TokenKind::Punctuation,
+            ',' | ';' => TokenKind::Punctuation,
+            ':' => {
+                self.eat(':');
+                TokenKind::Punctuation
+            }
+            '-' => {
+                self.eat('>');
+                TokenKind::Operator
+            }
+            '*' => {
+                self.eat('*');
+                TokenKind::Operator
+            }
+            '=' | '!' | '<' | '>' => {
+                self.eat('=');
+                TokenKind::Operator
+            }
+            '&' if self.eat('&') => TokenKind::Operator,
+            '|' if self.eat('|') || self.eat('>') => TokenKind::Operator,
+            '+' | '−' | '·' | '⋅' | '×' | '/' | '÷' | '^' | '@' | '?' | '→' | '➞' | '≤' | '≥'
+            | '≠' | '⩵' | '…' => TokenKind::Operator,
+            '⁻' if self.peek().is_some_and(is_exponent_char) => {
+                self.advance();
+                TokenKind::Operator
+            }
+            c if is_exponent_char(c) => TokenKind::Operator,
+            c if is_identifier_start(c) => {
+                self.eat_while(is_identifier_continue);
+                TokenKind::Identifier
+            }
+            _ => TokenKind::Invalid,
+        }
+    }
+}
+
+/// Splits `text` into tokens covering every byte of it, in order.
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut lexer = Lexer {
+        text,
+        pos: 0,
+        scopes: Vec::new(),
+        tokens: Vec::new(),
+    };
+    while lexer.pos < text.len() {
+        let start = lexer.pos;
+        let kind = lexer.next_token();
+        lexer.tokens.push(Token {
+            kind,
+            range: start..lexer.pos,
+        });
+    }
+    lexer.tokens
+}
+
+// ---------------------------------------------------------------------------
+// Brackets
+
+/// The closing counterpart of an opening bracket or quote.
+pub fn closing_pair(open: char) -> Option<char> {
+    match open {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        '"' => Some('"'),
+        _ => None,
+    }
+}
+
+/// Byte ranges of matched bracket pairs and of brackets without a partner.
+/// Braces of string interpolations are part of the string and not counted.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Brackets {
+    pub pairs: Vec<(Range<usize>, Range<usize>)>,
+    pub unmatched: Vec<Range<usize>>,
+}
+
+impl Brackets {
+    /// The pair touching the cursor (byte offset): a bracket right before
+    /// the cursor wins over one right after it.
+    pub fn pair_at(&self, cursor: usize) -> Option<&(Range<usize>, Range<usize>)> {
+        let touches = |end: bool| {
+            self.pairs.iter().find(move |(open, close)| {
+                if end {
+                    open.end == cursor || close.end == cursor
+                } else {
+                    open.start == cursor || close.start == cursor
+                }
+            })
+        };
+        touches(true).or_else(|| touches(false))
+    }
+}
+
+pub fn match_brackets(text: &str, tokens: &[Token]) -> Brackets {
+    let mut brackets = Brackets::default();
+    let mut stack: Vec<&Token> = Vec::new();
+    for token in tokens {
+        match token.kind {
+            TokenKind::OpenBracket => stack.push(token),
+            TokenKind::CloseBracket => {
+                let close = text[token.range.clone()].chars().next();
+                let matches = stack.last().is_some_and(|open| {
+                    text[open.range.clone()]
+                        .chars()
+                        .next()
+                        .and_then(closing_pair)
+                        == close
+                });
+                if matches {
+                    let open = stack.pop().expect("checked above");
+                    brackets
+                        .pairs
+                        .push((open.range.clone(), token.range.clone()));
+                } else {
+                    brackets.unmatched.push(token.range.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    brackets
+        .unmatched
+        .extend(stack.into_iter().map(|t| t.range.clone()));
+    brackets
+}
+
+// ---------------------------------------------------------------------------
+// Environment lookup
+
+/// What a name refers to in the current environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameKind {
+    Unit,
+    Function,
+    Variable,
+    Dimension,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct UnitPrefixes {
+    short: bool,
+    long: bool,
+    metric: bool,
+    binary: bool,
+}
+
+/// Metric and binary prefixes as `(long, short forms, is_binary)`, as in
+/// numbat's prefix parser.
+const PREFIXES: &[(&str, &[&str], bool)] = &[
+    ("quecto", &["q"], false),
+    ("ronto", &["r"], false),
+    ("yocto", &["y"], false),
+    ("zepto", &["z"], false),
+    ("atto", &["a"], false),
+    ("femto", &["f"], false),
+    ("pico", &["p"], false),
+    ("nano", &["n"], false),
+    ("micro", &["µ", "μ", "u"], false),
+    ("milli", &["m"], false),
+    ("centi", &["c"], false),
+    ("deci", &["d"], false),
+    ("deca", &["da"], false),
+    ("hecto", &["h"], false),
+    ("kilo", &["k"], false),
+    ("mega", &["M"], false),
+    ("giga", &["G"], false),
+    ("tera", &["T"], false),
+    ("peta", &["P"], false),
+    ("exa", &["E"], false),
+    ("zetta", &["Z"], false),
+    ("yotta", &["Y"], false),
+    ("ronna", &["R"], false),
+    ("quetta", &["Q"], false),
+    ("kibi", &["Ki"], true),
+    ("mebi", &["Mi"], true),
+    ("gibi", &["Gi"], true),
+    ("tebi", &["Ti"], true),
+    ("pebi", &["Pi"], true),
+    ("exbi", &["Ei"], true),
+    ("zebi", &["Zi"], true),
+    ("yobi", &["Yi"], true),
+    ("robi", &["Ri"], true),
+    ("quebi", &["Qi"], true),
+];
+
+/// The names known to a numbat context, for classifying identifiers
+/// without evaluating anything. Cheap to query; rebuilt by the session
+/// whenever definitions may have changed.
+#[derive(Debug, Default)]
+pub struct Names {
+    variables: HashSet<String>,
+    functions: HashSet<String>,
+    dimensions: HashSet<String>,
+    /// Unit aliases and the prefixes they accept.
+    units: HashMap<String, UnitPrefixes>,
+}
+
+impl Names {
+    pub fn from_context(context: &numbat::Context) -> Self {
+        let mut units = HashMap::new();
+        for (_, (_, meta)) in context.unit_representations() {
+            for (alias, accepts) in &meta.aliases {
+                units.insert(
+                    alias.to_string(),
+                    UnitPrefixes {
+                        short: accepts.short,
+                        long: accepts.long,
+                        metric: meta.metric_prefixes,
+                        binary: meta.binary_prefixes,
+                    },
+                );
+            }
+        }
+        Self {
+            variables: context.variable_names().map(|n| n.to_string()).collect(),
+            functions: context.function_names().map(|n| n.to_string()).collect(),
+            dimensions: context
+                .dimension_names()
+                .iter()
+                .map(|n| n.to_string())
+                .collect(),
+            units,
+        }
+    }
+
+    pub fn classify(&self, name: &str) -> NameKind {
+        if self.variables.contains(name) || RESERVED_VARIABLES.contains(&name) {
+            NameKind::Variable
+        } else if TEMPERATURE_PSEUDO_UNITS.contains(&name) || self.is_unit(name) {
+            NameKind::Unit
+        } else if self.functions.contains(name) {
+            NameKind::Function
+        } else if self.dimensions.contains(name) {
+            NameKind::Dimension
+        } else {
+            NameKind::Unknown
+        }
+    }
+
+    /// A unit alias, possibly with a metric or binary prefix (`km`, `MiB`,
+    /// `kilometer`).
+    fn is_unit(&self, name: &str) -> bool {
+        if self.units.contains_key(name) {
+            return true;
+        }
+        PREFIXES.iter().any(|(long, shorts, binary)| {
+            let accepts = |unit: &str, long_form: bool| {
+                self.units.get(unit).is_some_and(|p| {
+                    (if long_form { p.long } else { p.short })
+                        && if *binary { p.binary } else { p.metric }
+                })
+            };
+            name.strip_prefix(long)
+                .is_some_and(|unit| accepts(unit, true))
+                || shorts.iter().any(|short| {
+                    name.strip_prefix(short)
+                        .is_some_and(|unit| accepts(unit, false))
+                })
+        })
+    }
+}
+
+/// The names `text` defines (`let x`, `fn f`, `unit u`, …) and the names
+/// it refers to otherwise — units and parameters included, which is
+/// harmless for telling which entries depend on a changed definition.
+pub fn dependencies(text: &str) -> (Vec<&str>, Vec<&str>) {
+    let tokens = tokenize(text);
+    let identifiers: Vec<&str> = tokens
+        .iter()
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .map(|t| match t.kind {
+            TokenKind::Identifier => &text[t.range.clone()],
+            _ => "",
+        })
+        .collect();
+    let mut defines = Vec::new();
+    let mut uses = Vec::new();
+    for (i, name) in identifiers.iter().enumerate() {
+        if name.is_empty() || is_keyword(name) || is_type_name(name) {
+            continue;
+        }
+        let defined = i > 0
+            && matches!(
+                identifiers[i - 1],
+                "let" | "fn" | "unit" | "dimension" | "struct"
+            );
+        if defined {
+            defines.push(*name);
+        } else if !uses.contains(name) {
+            uses.push(*name);
+        }
+    }
+    (defines, uses)
+}
+
+/// The words of the `sweep v from 1 m to 2 m in 5 steps: …` command.
+pub const SWEEP_WORDS: &[&str] = &["sweep", "from", "steps"];
+
+/// How a locally introduced name is used, for names defined by the line
+/// itself (`let x = …`, `fn f(x) = …`, `sweep x from …`) that the
+/// environment does not know about yet.
+pub fn local_definitions<'t>(text: &'t str, tokens: &[Token]) -> HashMap<&'t str, NameKind> {
+    let mut defined = HashMap::new();
+    let significant: Vec<&Token> = tokens
+        .iter()
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .collect();
+    let lexeme = |t: &Token| &text[t.range.clone()];
+
+    for (i, token) in significant.iter().enumerate() {
+        if token.kind != TokenKind::Identifier {
+            continue;
+        }
+        let Some(next) = significant.get(i + 1) else {
+            continue;
+        };
+        if next.kind != TokenKind::Identifier {
+            continue;
+        }
+        let kind = match lexeme(token) {
+            "let" | "where" | "and" => NameKind::Variable,
+            "sweep" if i == 0 => NameKind::Variable,
+            "fn" => NameKind::Function,
+            "unit" => NameKind::Unit,
+            "dimension" | "struct" => NameKind::Dimension,
+            _ => continue,
+        };
+        defined.insert(lexeme(next), kind);
+
+        // Parameters of a function definition: `fn f(a: Length, b) = …`.
+        if kind == NameKind::Function {
+            let mut depth = 0;
+            for (j, t) in significant.iter().enumerate().skip(i + 2) {
+                match (t.kind, lexeme(t)) {
+                    (TokenKind::OpenBracket, _) => depth += 1,
+                    (TokenKind::CloseBracket, _) => {
+                        depth -= 1;
+                        if depth <= 0 {
+                            break;
+                        }
+                    }
+                    (TokenKind::Identifier, name) if depth == 1 => {
+                        let ends_parameter = significant
+                            .get(j + 1)
+                            .is_some_and(|n| matches!(lexeme(n), ":" | "," | ")"));
+                        let starts_parameter = matches!(lexeme(significant[j - 1]), "(" | ",");
+                        if ends_parameter && starts_parameter {
+                            defined.insert(name, NameKind::Variable);
+                        }
+                    }
+                    (TokenKind::Operator, "=") if depth == 0 => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+    defined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<(&str, TokenKind)> {
+        tokenize(text)
+            .into_iter()
+            .filter(|t| t.kind != TokenKind::Whitespace)
+            .map(|t| (&text[t.range], t.kind))
+            .collect()
+    }
+
+    #[test]
+    fn tokens_cover_the_whole_input() {
+        let text = "let x = 2 m + \"a{b:.2}c\" # note";
+        let tokens = tokenize(text);
+        let mut end = 0;
+        for token in &tokens {
+            assert_eq!(token.range.start, end);
+            end = token.range.end;
+        }
+        assert_eq!(end, text.len());
+    }
+
+    #[test]
+    fn lexes_number_literals() {
+        assert_eq!(kinds("1e-3"), [("1e-3", TokenKind::Number)]);
+        assert_eq!(kinds("0x1F"), [("0x1F", TokenKind::Number)]);
+        assert_eq!(kinds(".5"), [(".5", TokenKind::Number)]);
+        assert_eq!(kinds("1_000.25"), [("1_000.25", TokenKind::Number)]);
+        // `2e` is two times Euler's number, not an incomplete exponent.
+        assert_eq!(
+            kinds("2e"),
+            [("2", TokenKind::Number), ("e", TokenKind::Identifier)]
+        );
+    }
+
+    #[test]
+    fn lexes_unicode_identifiers_and_operators() {
+        assert_eq!(
+            kinds("20 °C → °F"),
+            [
+                ("20", TokenKind::Number),
+                ("°C", TokenKind::Identifier),
+                ("→", TokenKind::Operator),
+                ("°F", TokenKind::Identifier),
+            ]
+        );
+        assert_eq!(
+            kinds("m²"),
+            [("m", TokenKind::Identifier), ("²", TokenKind::Operator)]
+        );
+    }
+
+    #[test]
+    fn lexes_string_interpolation() {
+        assert_eq!(
+            kinds("\"r = {r:.2} m\""),
+            [
+                ("\"r = {", TokenKind::String),
+                ("r", TokenKind::Identifier),
+                (":.2", TokenKind::String),
+                ("} m\"", TokenKind::String),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_string_runs_to_the_end() {
+        assert_eq!(kinds("\"abc"), [("\"abc", TokenKind::String)]);
+    }
+
+    #[test]
+    fn classifies_names() {
+        let names = {
+            let mut e = crate::engine::Engine::new(numbat::FormatOptions::default());
+            assert!(e.eval("let mass = 3 kg").error.is_none());
+            e.names()
+        };
+        assert_eq!(names.classify("km"), NameKind::Unit);
+        assert_eq!(names.classify("kilometer"), NameKind::Unit);
+        assert_eq!(names.classify("MiB"), NameKind::Unit);
+        assert_eq!(names.classify("°C"), NameKind::Unit);
+        assert_eq!(names.classify("sqrt"), NameKind::Function);
+        assert_eq!(names.classify("mass"), NameKind::Variable);
+        assert_eq!(names.classify("Length"), NameKind::Dimension);
+        assert_eq!(names.classify("nonsense_name"), NameKind::Unknown);
+    }
+
+    #[test]
+    fn matches_brackets() {
+        let text = "max([1, (2 m)]) )";
+        let brackets = match_brackets(text, &tokenize(text));
+        assert_eq!(brackets.pairs.len(), 3);
+        assert_eq!(brackets.unmatched, vec![16..17]);
+        // Cursor right after `(2 m)` selects that pair.
+        assert_eq!(brackets.pair_at(13), Some(&(8..9, 12..13)));
+
+        let text = "sqrt((2";
+        let brackets = match_brackets(text, &tokenize(text));
+        assert_eq!(brackets.unmatched, [4..5, 5..6]);
+    }
+
+    #[test]
+    fn interpolation_braces_are_not_brackets() {
+        let text = "\"{x}\"";
+        assert_eq!(match_brackets(text, &tokenize(text)), Brackets::default());
+    }
+
+    #[test]
+    fn finds_local_definitions() {
+        let text = "fn area(w: Length, h) = w * h";
+        let defined = local_definitions(text, &tokenize(text));
+        assert_eq!(defined.get("area"), Some(&NameKind::Function));
+        assert_eq!(defined.get("w"), Some(&NameKind::Variable));
+        assert_eq!(defined.get("h"), Some(&NameKind::Variable));
+        assert_eq!(defined.get("Length"), None);
+
+        let (defines, uses) = dependencies("let speed = distance / time -> km/h");
+        assert_eq!(defines, ["speed"]);
+        assert_eq!(uses, ["distance", "time", "km", "h"]);
+        assert_eq!(dependencies("fn f(x) = x * mass").0, ["f"]);
+
+        let text = "sweep v from 1 m/s to 2 m/s in 3 steps: v * 2 s";
+        let defined = local_definitions(text, &tokenize(text));
+        assert_eq!(defined.get("v"), Some(&NameKind::Variable));
+    }
+}
@@ -7,6 +7,7 @@ use egui::{Color32, CornerRadius, FontFamily, FontId, Stroke, TextFormat};
 use numbat::markup::{FormatType, FormattedString, Markup};
 
 use crate::config::ThemeChoice;
+use crate::syntax::{self, NameKind, Names, TokenKind};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Palette {
@@ -38,6 +39,10 @@ pub struct Palette {
     pub type_id: Color32,
     pub operator: Color32,
     pub decorator: Color32,
+
+    // Additional syntax colors for the input line.
+    pub function: Color32,
+    pub variable: Color32,
 }
 
 pub const DARK: Palette = Palette {
@@ -58,6 +63,8 @@ pub const DARK: Palette = Palette {
     type_id: Color32::from_rgb(0x84, 0xab, 0xf5),
     operator: Color32::from_rgb(0xb4, 0xba, 0xc8),
     decorator: Color32::from_rgb(0xa3, 0xd7, 0x8f),
+    function: Color32::from_rgb(0x8c, 0xd0, 0xf5),
+    variable: Color32::from_rgb(0xe8, 0xa8, 0x7c),
 };
 
 pub const LIGHT: Palette = Palette {
@@ -78,6 +85,8 @@ pub const LIGHT: Palette = Palette {
     type_id: Color32::from_rgb(0x34, 0x5c, 0xc0),
     operator: Color32::from_rgb(0x50, 0x56, 0x64),
     decorator: Color32::from_rgb(0x46, 0x86, 0x30),
+    function: Color32::from_rgb(0x1d, 0x74, 0xb0),
+    variable: Color32::from_rgb(0xb0, 0x55, 0x22),
 };
 
 /// Resolves the configured theme against the system preference.
@@ -199,64 +208,106 @@ pub fn markup_job(markup: &Markup, palette: &Palette, font_size: f32) -> LayoutJ
 }
 
 /// Syntax highlighting for the *input* line while typing (numbat markup is
-/// only available after evaluation, so this is a small standalone lexer).
-pub fn highlight_input(text: &str, palette: &Palette, font_size: f32) -> LayoutJob {
+/// only available after evaluation). Identifiers are looked up in `names`,
+/// so units, functions and variables get their own colors and unknown
+/// names are underlined before the line is evaluated. Brackets without a
+/// partner are flagged too; with a `cursor` (byte offset), the bracket pair
+/// touching it is marked.
+pub fn highlight_input(
+    text: &str,
+    cursor: Option<usize>,
+    names: &Names,
+    palette: &Palette,
+    font_size: f32,
+) -> LayoutJob {
     let mut job = LayoutJob::default();
     let font_id = FontId::new(font_size, FontFamily::Monospace);
-    let mut push = |slice: &str, color: Color32| {
-        if !slice.is_empty() {
-            job.append(
-                slice,
-                0.0,
-                TextFormat {
-                    font_id: font_id.clone(),
-                    color,
-                    ..Default::default()
-                },
-            );
-        }
-    };
 
-    let bytes = text.as_bytes();
-    let mut i = 0;
-    while i < bytes.len() {
-        let rest = &text[i..];
-        let c = rest.chars().next().unwrap();
+    let tokens = syntax::tokenize(text);
+    let local = syntax::local_definitions(text, &tokens);
+    let brackets = syntax::match_brackets(text, &tokens);
+    let active_pair = cursor.and_then(|c| brackets.pair_at(c));
+    let mut previous: Option<&str> = None;
+    let sweep = text.trim_start().starts_with("sweep ");
+    // Open brackets, to tell struct fields (`Point {x: 1}`) from names.
+    let mut open: Vec<&str> = Vec::new();
 
-        if c == '#' {
-            push(rest, palette.text_faint);
-            break;
-        } else if c == '"' {
-            let end = rest[1..].find('"').map(|p| i + p + 2).unwrap_or(text.len());
-            push(&text[i..end], palette.string);
-            i = end;
-        } else if c.is_ascii_digit()
-            || (c == '.' && rest[1..].starts_with(|d: char| d.is_ascii_digit()))
-        {
-            let end = i + rest
-                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '.' || ch == '_'))
-                .unwrap_or(rest.len());
-            push(&text[i..end], palette.value);
-            i = end;
-        } else if c.is_alphabetic() || c == '_' {
-            let end = i + rest
-                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
-                .unwrap_or(rest.len());
-            let word = &text[i..end];
-            let color = if numbat::keywords::KEYWORDS.contains(&word) {
-                palette.keyword
-            } else {
-                palette.text
-            };
-            push(word, color);
-            i = end;
-        } else if "+-*/^=<>!·×÷²³→➞".contains(c) {
-            push(&text[i..i + c.len_utf8()], palette.operator);
-            i += c.len_utf8();
-        } else {
-            push(&text[i..i + c.len_utf8()], palette.text);
-            i += c.len_utf8();
+    for (i, token) in tokens.iter().enumerate() {
+        let lexeme = &text[token.range.clone()];
+        let mut format = TextFormat {
+            font_id: font_id.clone(),
+            color: palette.text,
+            ..Default::default()
+        };
+
+        format.color = match token.kind {
+            TokenKind::Whitespace | TokenKind::Punctuation => palette.text,
+            TokenKind::OpenBracket | TokenKind::CloseBracket => {
+                if brackets.unmatched.contains(&token.range) {
+                    format.underline = Stroke::new(1.0, palette.error);
+                    palette.error
+                } else if active_pair
+                    .is_some_and(|(open, close)| *open == token.range || *close == token.range)
+                {
+                    format.background = palette.accent.gamma_multiply(0.3);
+                    palette.text
+                } else {
+                    palette.text_dim
+                }
+            }
+            TokenKind::Comment => palette.text_faint,
+            TokenKind::Number => palette.value,
+            TokenKind::String => palette.string,
+            TokenKind::Operator => palette.operator,
+            TokenKind::Invalid => palette.error,
+            TokenKind::Identifier => {
+                let next = tokens[i + 1..]
+                    .iter()
+                    .find(|t| t.kind != TokenKind::Whitespace)
+                    .map(|t| &text[t.range.clone()]);
+                match previous {
+                    Some("@") => palette.decorator,
+                    // Struct fields and module paths (`use units::si`).
+                    Some("." | "::" | "use") => palette.text,
+                    _ if next == Some("::") => palette.text,
+                    _ if next == Some(":") && open.last() == Some(&"{") => palette.text,
+                    _ if syntax::is_keyword(lexeme) => palette.keyword,
+                    _ if sweep && syntax::SWEEP_WORDS.contains(&lexeme) => palette.keyword,
+                    _ if syntax::is_type_name(lexeme) => palette.type_id,
+                    _ => {
+                        let kind = local
+                            .get(lexeme)
+                            .copied()
+                            .unwrap_or_else(|| names.classify(lexeme));
+                        match kind {
+                            NameKind::Unit => palette.unit,
+                            NameKind::Function => palette.function,
+                            NameKind::Variable => palette.variable,
+                            NameKind::Dimension => palette.type_id,
+                            // A word at the very end is most likely still
+                            // being typed; flag it only once it is complete.
+                            NameKind::Unknown if token.range.end == text.len() => palette.text,
+                            NameKind::Unknown => {
+                                format.underline = Stroke::new(1.0, palette.error);
+                                palette.error
+                            }
+                        }
+                    }
+                }
+            }
+        };
+
+        match token.kind {
+            TokenKind::OpenBracket => open.push(lexeme),
+            TokenKind::CloseBracket => {
+                open.pop();
+            }
+            _ => {}
+        }
+        if token.kind != TokenKind::Whitespace {
+            previous = Some(lexeme);
         }
+        job.append(lexeme, 0.0, format);
     }
 
     job
@@ -0,0 +1,193 @@
+//! The documentation browser: every unit, function, constant and dimension
+//! the engine knows, grouped by module and filterable, with what `info`
+//! says about the selected one and an example to try in the prompt.
+
+use std::sync::Arc;
+
+use egui::{CornerRadius, Frame, Margin, RichText, Stroke};
+use numbat::markup::Markup;
+
+use crate::app::NumbatApp;
+use crate::engine::{builtin_catalog, CatalogItem, USER_MODULE};
+use crate::syntax::{NameKind, Names};
+use crate::theme::markup_job;
+use crate::ui::insert_at_cursor;
+
+#[derive(Default)]
+pub struct Browser {
+    pub filter: String,
+    /// What the session defined, and the names it was collected for;
+    /// rebuilt when they change (the prelude's items never do).
+    user_catalog: Option<(Arc<Names>, Arc<Vec<CatalogItem>>)>,
+    /// The selected item and its `info`.
+    selected: Option<(CatalogItem, Markup)>,
+}
+
+impl NumbatApp {
+    pub fn browser_panel(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        let font_size = self.config.ui.font_size;
+        egui::Panel::right("browser_panel")
+            .frame(
+                Frame::new()
+                    .fill(palette.bg_raised)
+                    .stroke(Stroke::new(1.0, palette.border))
+                    .inner_margin(Margin::same(14)),
+            )
+            .resizable(true)
+            .default_size(360.0)
+            .min_size(260.0)
+            .show_separator_line(false)
+            .show_inside(ui, |ui| {
+                ui.spacing_mut().item_spacing.y = 8.0;
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new("DOCUMENTATION")
+                            .size(11.0)
+                            .strong()
+                            .color(palette.text_faint),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let close = egui::Button::new(
+                            RichText::new("×").size(15.0).color(palette.text_dim),
+                        )
+                        .frame(false);
+                        if ui.add(close).on_hover_text("Close").clicked() {
+                            self.show_browser = false;
+                        }
+                    });
+                });
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.browser.filter)
+                        .hint_text("Filter by name, dimension or module")
+                        .desired_width(f32::INFINITY),
+                );
+
+                let mut insert = None;
+                if let Some((item, info)) = &self.browser.selected {
+                    Frame::new()
+                        .fill(palette.card)
+                        .stroke(Stroke::new(1.0, palette.border))
+                        .corner_radius(CornerRadius::same(8))
+                        .inner_margin(Margin::same(10))
+                        .show(ui, |ui| {
+                            ui.set_width(ui.available_width());
+                            egui::ScrollArea::vertical()
+                                .id_salt("browser_info")
+                                .max_height(200.0)
+                                .show(ui, |ui| {
+                                    ui.label(markup_job(info, &palette, font_size - 1.0));
+                                });
+                            let button = egui::Button::new(
+                                RichText::new(format!("⤵ {}", item.example)).monospace(),
+                            );
+                            if ui
+                                .add(button)
+                                .on_hover_text("Insert into the prompt")
+                                .clicked()
+                            {
+                                insert = Some(item.example.clone());
+                            }
+                        });
+                }
+
+                let names = self.session.names();
+                let user_catalog = match &self.browser.user_catalog {
+                    Some((built_for, catalog)) if Arc::ptr_eq(built_for, &names) => {
+                        Arc::clone(catalog)
+                    }
+                    _ => {
+                        let catalog = Arc::new(self.session.engine.user_catalog());
+                        self.browser.user_catalog = Some((names, Arc::clone(&catalog)));
+                        catalog
+                    }
+                };
+                let filter = self.browser.filter.trim().to_lowercase();
+                let matches = |item: &CatalogItem| {
+                    [&item.name, &item.detail, &item.module]
+                        .into_iter()
+                        .chain(&item.aliases)
+                        .any(|text| text.to_lowercase().contains(&filter))
+                };
+
+                let mut selected = None;
+                egui::ScrollArea::vertical()
+                    .id_salt("browser_list")
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        // Both catalogs are sorted by module, and share no module.
+                        let same_module = |a: &CatalogItem, b: &CatalogItem| a.module == b.module;
+                        let groups = user_catalog
+                            .chunk_by(same_module)
+                            .chain(builtin_catalog().chunk_by(same_module));
+                        for group in groups {
+                            let module = &group[0].module;
+                            let shown: Vec<&CatalogItem> =
+                                group.iter().filter(|item| matches(item)).collect();
+                            if shown.is_empty() {
+                                continue;
+                            }
+                            let title = format!("{module}  ({})", shown.len());
+                            egui::CollapsingHeader::new(
+                                RichText::new(title).size(12.5).color(palette.text_dim),
+                            )
+                            .id_salt(module)
+                            .default_open(module == USER_MODULE)
+                            .open((!filter.is_empty()).then_some(true))
+                            .show(ui, |ui| {
+                                for item in shown {
+                                    let is_selected = self
+                                        .browser
+                                        .selected
+                                        .as_ref()
+                                        .is_some_and(|(s, _)| s == item);
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new(kind_label(item.kind))
+                                                .size(10.5)
+                                                .color(palette.text_faint),
+                                        );
+                                        let name = ui.selectable_label(
+                                            is_selected,
+                                            RichText::new(&item.name).monospace(),
+                                        );
+                                        if name.clicked() {
+                                            selected = Some(item.clone());
+                                        }
+                                        ui.add(
+                                            egui::Label::new(
+                                                RichText::new(&item.detail)
+                                                    .size(11.5)
+                                                    .color(palette.text_dim),
+                                            )
+                                            .truncate(),
+                                        );
+                                    });
+                                }
+                            });
+                        }
+                    });
+
+                if let Some(item) = selected {
+                    let info = self.session.engine.info_markup(&item.name);
+                    self.browser.selected = Some((item, info));
+                }
+                if let Some(example) = insert {
+                    let input_id = self.main_input_id();
+                    insert_at_cursor(ui.ctx(), input_id, &mut self.session.input, &example);
+                    self.session.on_input_edited();
+                    ui.ctx().memory_mut(|m| m.request_focus(input_id));
+                }
+            });
+    }
+}
+
+fn kind_label(kind: NameKind) -> &'static str {
+    match kind {
+        NameKind::Unit => "unit",
+        NameKind::Function => "fn",
+        NameKind::Variable => "const",
+        NameKind::Dimension => "dim",
+        NameKind::Unknown => "",
+    }
+}
@@ -0,0 +1,120 @@
+//! The keyboard shortcut overlay (F1), generated from the same tables that
+//! dispatch the shortcuts, so it always shows the current bindings.
+
+use egui::{CornerRadius, Frame, Margin, RichText, Stroke};
+
+use crate::actions::{self, Action, Scope};
+use crate::app::NumbatApp;
+use crate::config::Keymap;
+use crate::ui::readline;
+
+/// Keys of the prompt that are not configurable.
+const PROMPT_KEYS: &[(&str, &str)] = &[
+    ("Enter", "Evaluate"),
+    ("Tab", "Complete, or expand a \\symbol escape"),
+    ("↑ / ↓", "Previous / next history entry"),
+    ("Esc", "Close the completion list"),
+];
+
+impl NumbatApp {
+    /// Draws the overlay over the main window while it is open.
+    pub fn cheat_sheet_ui(&mut self, ctx: &egui::Context) {
+        if !self.show_cheat_sheet {
+            return;
+        }
+        let palette = self.palette;
+        let keybindings = &self.config.keybindings;
+
+        let row = |ui: &mut egui::Ui, keys: String, description: &str| {
+            ui.label(
+                RichText::new(keys)
+                    .monospace()
+                    .size(12.0)
+                    .color(palette.accent),
+            );
+            ui.label(RichText::new(description).size(12.5).color(palette.text));
+            ui.end_row();
+        };
+        let heading = |ui: &mut egui::Ui, text: &str| {
+            ui.add_space(8.0);
+            ui.label(
+                RichText::new(text)
+                    .size(11.0)
+                    .strong()
+                    .color(palette.text_faint),
+            );
+        };
+
+        let modal = egui::Modal::new(egui::Id::new("cheat_sheet"))
+            .frame(
+                Frame::new()
+                    .fill(palette.card)
+                    .stroke(Stroke::new(1.0, palette.border))
+                    .corner_radius(CornerRadius::same(12))
+                    .inner_margin(Margin::same(16)),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(440.0);
+                ui.label(
+                    RichText::new("Keyboard Shortcuts")
+                        .size(15.0)
+                        .strong()
+                        .color(palette.text),
+                );
+                egui::ScrollArea::vertical()
+                    .max_height(ctx.content_rect().height() * 0.7)
+                    .show(ui, |ui| {
+                        for scope in [Scope::MainWindow, Scope::QuickPanel] {
+                            heading(ui, &scope.label().to_uppercase());
+                            egui::Grid::new(("cheat_sheet", scope.label()))
+                                .num_columns(2)
+                                .spacing([16.0, 4.0])
+                                .show(ui, |ui| {
+                                    for action in Action::bindable() {
+                                        if action.scope() != scope {
+                                            continue;
+                                        }
+                                        let keys: Vec<String> =
+                                            actions::bindings(keybindings, &action)
+                                                .iter()
+                                                .map(|s| ctx.format_shortcut(s))
+                                                .collect();
+                                        if !keys.is_empty() {
+                                            row(ui, keys.join(", "), &action.title());
+                                        }
+                                    }
+                                });
+                        }
+
+                        heading(ui, "PROMPT");
+                        egui::Grid::new("cheat_sheet_prompt")
+                            .num_columns(2)
+                            .spacing([16.0, 4.0])
+                            .show(ui, |ui| {
+                                for (keys, description) in PROMPT_KEYS {
+                                    row(ui, (*keys).to_owned(), description);
+                                }
+                                if self.config.input.keymap == Keymap::Emacs {
+                                    for (shortcut, command) in readline::shortcuts() {
+                                        row(
+                                            ui,
+                                            ctx.format_shortcut(&shortcut),
+                                            command.description(),
+                                        );
+                                    }
+                                }
+                            });
+                    });
+                ui.add_space(8.0);
+                ui.label(
+                    RichText::new("Shortcuts can be changed in the settings.")
+                        .size(11.5)
+                        .color(palette.text_faint),
+                );
+            });
+
+        if modal.should_close() {
+            self.show_cheat_sheet = false;
+        }
+    }
+}
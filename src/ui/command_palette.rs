@@ -0,0 +1,236 @@
+//! The command palette (⌘/Ctrl+Shift+P): fuzzy search over every action
+//! of the registry in `actions.rs`, with shortcut hints.
+
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Align, CornerRadius, FontId, Frame, Key, Layout, Margin, RichText, Stroke};
+
+use crate::actions::{self, Action};
+use crate::app::NumbatApp;
+
+/// The palette while it is open.
+pub struct CommandPalette {
+    query: String,
+    selected: usize,
+    /// Snapshot of the available actions, taken when opened.
+    actions: Vec<Action>,
+    just_opened: bool,
+}
+
+/// Matches `query` against `text` as a case-insensitive subsequence.
+/// Matches at word starts and runs of consecutive characters score higher.
+/// Returns the score and the matched character indices.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(i32, Vec<usize>)> {
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut matched = Vec::new();
+    let mut next = 0;
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let q = q.to_lowercase().next()?;
+        let index = (next..text.len()).find(|&i| text[i].to_lowercase().next() == Some(q))?;
+        score += 1;
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 4;
+        }
+        if matched.last().is_some_and(|&last| last + 1 == index) {
+            score += 3;
+        }
+        score -= (index - next).min(4) as i32;
+        matched.push(index);
+        next = index + 1;
+    }
+    Some((score, matched))
+}
+
+impl NumbatApp {
+    pub fn open_command_palette(&mut self) {
+        let mut actions = Action::fixed();
+        actions.extend(
+            self.session
+                .checkpoints
+                .iter()
+                .rev()
+                .map(|checkpoint| Action::RestoreCheckpoint(checkpoint.name.clone())),
+        );
+        self.command_palette = Some(CommandPalette {
+            query: String::new(),
+            selected: 0,
+            actions,
+            just_opened: true,
+        });
+    }
+
+    /// Draws the palette over the main window while it is open.
+    pub fn command_palette_ui(&mut self, ctx: &egui::Context) {
+        let Some(mut palette_state) = self.command_palette.take() else {
+            return;
+        };
+        let palette = self.palette;
+        let font_size = self.config.ui.font_size;
+        let keybindings = &self.config.keybindings;
+
+        // Filter and rank.
+        let mut matches: Vec<(i32, Vec<usize>, Action)> = palette_state
+            .actions
+            .iter()
+            .filter_map(|action| {
+                let (score, indices) = fuzzy_match(&palette_state.query, &action.title())?;
+                Some((score, indices, action.clone()))
+            })
+            .collect();
+        matches.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+        palette_state.selected = palette_state.selected.min(matches.len().saturating_sub(1));
+
+        let (up, down, enter) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, Key::ArrowDown),
+                i.key_pressed(Key::Enter),
+            )
+        });
+        if up {
+            palette_state.selected = palette_state.selected.saturating_sub(1);
+        }
+        if down && palette_state.selected + 1 < matches.len() {
+            palette_state.selected += 1;
+        }
+
+        let mut chosen: Option<Action> = None;
+        let modal = egui::Modal::new(egui::Id::new("command_palette"))
+            .frame(
+                Frame::new()
+                    .fill(palette.card)
+                    .stroke(Stroke::new(1.0, palette.border))
+                    .corner_radius(CornerRadius::same(12))
+                    .inner_margin(Margin::same(10)),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(460.0);
+                let edit = ui.add(
+                    egui::TextEdit::singleline(&mut palette_state.query)
+                        .font(FontId::proportional(font_size + 1.0))
+                        .hint_text(RichText::new("Type a command…").color(palette.text_faint))
+                        .frame(Frame::NONE)
+                        .desired_width(f32::INFINITY),
+                );
+                if palette_state.just_opened {
+                    edit.request_focus();
+                    palette_state.just_opened = false;
+                }
+                if edit.changed() {
+                    // Filtered again next frame.
+                    palette_state.selected = 0;
+                    ui.ctx().request_repaint();
+                }
+                ui.separator();
+
+                if matches.is_empty() {
+                    ui.label(
+                        RichText::new("No matching command")
+                            .size(11.5)
+                            .color(palette.text_faint),
+                    );
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .show(ui, |ui| {
+                        for (i, (_, indices, action)) in matches.iter().enumerate() {
+                            let selected = i == palette_state.selected;
+                            let shortcut = actions::shortcut_label(ui.ctx(), keybindings, action);
+                            let row = command_row(
+                                ui, action, indices, shortcut, selected, &palette, font_size,
+                            );
+                            if selected && (up || down) {
+                                row.scroll_to_me(None);
+                            }
+                            if row.clicked() {
+                                chosen = Some(action.clone());
+                            }
+                        }
+                    });
+                if enter {
+                    if let Some((_, _, action)) = matches.get(palette_state.selected) {
+                        chosen = Some(action.clone());
+                    }
+                }
+            });
+
+        match chosen {
+            Some(action) => self.run_action(ctx, action),
+            None if modal.should_close() => {}
+            None => self.command_palette = Some(palette_state),
+        }
+    }
+}
+
+/// One palette row: the title with the matched characters highlighted,
+/// and the shortcut on the right.
+fn command_row(
+    ui: &mut egui::Ui,
+    action: &Action,
+    indices: &[usize],
+    shortcut: Option<String>,
+    selected: bool,
+    palette: &crate::theme::Palette,
+    font_size: f32,
+) -> egui::Response {
+    let mut job = LayoutJob::default();
+    for (i, c) in action.title().chars().enumerate() {
+        let color = if indices.contains(&i) {
+            palette.accent
+        } else {
+            palette.text
+        };
+        job.append(
+            c.encode_utf8(&mut [0; 4]),
+            0.0,
+            TextFormat::simple(FontId::proportional(font_size), color),
+        );
+    }
+
+    let frame = Frame::new()
+        .fill(if selected {
+            palette.bg_raised
+        } else {
+            egui::Color32::TRANSPARENT
+        })
+        .corner_radius(CornerRadius::same(6))
+        .inner_margin(Margin::symmetric(8, 5));
+    frame
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.horizontal(|ui| {
+                ui.label(job);
+                if let Some(shortcut) = shortcut {
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        ui.label(
+                            RichText::new(shortcut)
+                                .size(font_size - 2.0)
+                                .color(palette.text_faint),
+                        );
+                    });
+                }
+            });
+        })
+        .response
+        .interact(egui::Sense::click())
+        .on_hover_cursor(egui::CursorIcon::PointingHand)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_subsequences() {
+        assert!(fuzzy_match("clr", "Clear History").is_some());
+        assert!(fuzzy_match("xyz", "Clear History").is_none());
+        assert_eq!(fuzzy_match("", "Quit").map(|(s, _)| s), Some(0));
+    }
+
+    #[test]
+    fn prefers_word_starts() {
+        let score = |q, t| fuzzy_match(q, t).unwrap().0;
+        assert!(score("ch", "Clear History") > score("ch", "Restore Checkpoint: chem"));
+        assert!(score("set", "Settings…") > score("set", "Reset Session"));
+    }
+}
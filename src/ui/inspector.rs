@@ -0,0 +1,186 @@
+//! The inspector sidebar: the variables, functions, units and dimensions
+//! the session defined, searchable. Clicking a name inserts it into the
+//! prompt; sliders give numeric variables another value.
+
+use egui::{Frame, Margin, RichText, Stroke};
+
+use crate::app::NumbatApp;
+use crate::session::Definition;
+use crate::syntax::NameKind;
+use crate::theme::Palette;
+use crate::ui::insert_at_cursor;
+
+const SECTIONS: [(NameKind, &str); 4] = [
+    (NameKind::Variable, "Variables"),
+    (NameKind::Function, "Functions"),
+    (NameKind::Unit, "Units"),
+    (NameKind::Dimension, "Dimensions"),
+];
+
+#[derive(Default)]
+pub struct Inspector {
+    pub filter: String,
+    /// The variable whose slider is being dragged and its value so far;
+    /// it is redefined when the slider is let go.
+    dragging: Option<(String, f64)>,
+}
+
+impl NumbatApp {
+    pub fn inspector_panel(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        egui::Panel::left("inspector_panel")
+            .frame(
+                Frame::new()
+                    .fill(palette.bg_raised)
+                    .stroke(Stroke::new(1.0, palette.border))
+                    .inner_margin(Margin::same(14)),
+            )
+            .resizable(true)
+            .default_size(260.0)
+            .min_size(200.0)
+            .show_separator_line(false)
+            .show_inside(ui, |ui| {
+                ui.spacing_mut().item_spacing.y = 8.0;
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new("DEFINITIONS")
+                            .size(11.0)
+                            .strong()
+                            .color(palette.text_faint),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let close = egui::Button::new(
+                            RichText::new("×").size(15.0).color(palette.text_dim),
+                        )
+                        .frame(false);
+                        if ui.add(close).on_hover_text("Close").clicked() {
+                            self.show_inspector = false;
+                        }
+                    });
+                });
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.inspector.filter)
+                        .hint_text("Filter")
+                        .desired_width(f32::INFINITY),
+                );
+
+                let definitions = self.session.definitions();
+                if definitions.is_empty() {
+                    ui.label(
+                        RichText::new("Definitions like `let r = 2 m` show up here.")
+                            .size(12.0)
+                            .color(palette.text_faint),
+                    );
+                    return;
+                }
+                let filter = self.inspector.filter.trim().to_lowercase();
+                let mut insert = None;
+                let mut redefine = None;
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for (kind, title) in SECTIONS {
+                            let shown: Vec<&Definition> = definitions
+                                .iter()
+                                .filter(|d| d.kind == kind)
+                                .filter(|d| {
+                                    d.name.to_lowercase().contains(&filter)
+                                        || d.detail.to_lowercase().contains(&filter)
+                                })
+                                .collect();
+                            if shown.is_empty() {
+                                continue;
+                            }
+                            egui::CollapsingHeader::new(
+                                RichText::new(title).size(12.5).color(palette.text_dim),
+                            )
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                for definition in shown {
+                                    let row = definition_row(
+                                        ui,
+                                        definition,
+                                        &mut self.inspector.dragging,
+                                        &palette,
+                                    );
+                                    if row.0 {
+                                        insert = Some(definition.name.clone());
+                                    }
+                                    if let Some(value) = row.1 {
+                                        redefine = Some((definition.name.clone(), value));
+                                    }
+                                }
+                            });
+                        }
+                    });
+
+                if let Some(name) = insert {
+                    let input_id = self.main_input_id();
+                    insert_at_cursor(ui.ctx(), input_id, &mut self.session.input, &name);
+                    self.session.on_input_edited();
+                    ui.ctx().memory_mut(|m| m.request_focus(input_id));
+                }
+                if let Some((name, value)) = redefine {
+                    // The entries are re-run and may move.
+                    self.editing = None;
+                    self.session.redefine(&name, &value);
+                }
+            });
+    }
+}
+
+/// One definition: its name (clicked to insert it), what it is, and a
+/// slider for a numeric variable. Returns whether the name was clicked and
+/// the variable's new value, once a slider was let go.
+fn definition_row(
+    ui: &mut egui::Ui,
+    definition: &Definition,
+    dragging: &mut Option<(String, f64)>,
+    palette: &Palette,
+) -> (bool, Option<String>) {
+    let mut clicked = false;
+    ui.horizontal_wrapped(|ui| {
+        let name = egui::Button::new(
+            RichText::new(&definition.name)
+                .monospace()
+                .color(palette.accent),
+        )
+        .frame(false);
+        clicked = ui
+            .add(name)
+            .on_hover_text("Insert into the prompt")
+            .clicked();
+        ui.label(
+            RichText::new(&definition.detail)
+                .monospace()
+                .size(11.5)
+                .color(palette.text_dim),
+        );
+    });
+
+    let Some((value, unit)) = &definition.quantity else {
+        return (clicked, None);
+    };
+    let mut current = match dragging {
+        Some((name, dragged)) if *name == definition.name => *dragged,
+        _ => *value,
+    };
+    // Twice the value either way, so the range grows with each drag to
+    // the end.
+    let range = if *value > 0.0 {
+        0.0..=2.0 * value
+    } else {
+        2.0 * value..=0.0
+    };
+    let slider = ui.add(egui::Slider::new(&mut current, range).text(unit.as_str()));
+    let mut redefined = None;
+    if slider.dragged() {
+        *dragging = Some((definition.name.clone(), current));
+    } else if slider.changed() || slider.drag_stopped() {
+        *dragging = None;
+        if current != *value {
+            redefined = Some(format!("{current} {unit}").trim_end().to_owned());
+        }
+    }
+    (clicked, redefined)
+}
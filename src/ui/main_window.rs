@@ -2,9 +2,11 @@
 
 use egui::{Align, Frame, Layout, Margin, RichText, ScrollArea, Stroke};
 
+use crate::actions::{self, Action};
 use crate::app::NumbatApp;
+use crate::session::{with_conversion, Replay};
 use crate::theme::markup_job;
-use crate::ui::{entry_card, EntryAction, InputField};
+use crate::ui::{edit_card, entry_card, insert_at_cursor, symbols, EntryAction, InputField};
 
 /// Maximum width of the content column, for readability on wide windows.
 const CONTENT_MAX_WIDTH: f32 = 860.0;
@@ -14,6 +16,18 @@ impl NumbatApp {
         let palette = self.palette;
 
         self.input_panel(ui);
+        if self.show_plot {
+            self.plot_panel(ui);
+        }
+        if self.show_inspector {
+            self.inspector_panel(ui);
+        }
+        if self.show_browser {
+            self.browser_panel(ui);
+        }
+        if self.show_watches {
+            self.watch_panel(ui);
+        }
 
         // History fills the remaining space.
         egui::CentralPanel::default()
@@ -22,7 +36,9 @@ impl NumbatApp {
                 self.history_view(ui);
             });
 
-        self.toasts.ui(ui.ctx(), &palette, "main");
+        if let Some(action) = self.toasts.ui(ui.ctx(), &palette, "main") {
+            self.run_action(ui.ctx(), action);
+        }
     }
 
     fn history_view(&mut self, ui: &mut egui::Ui) {
@@ -30,9 +46,18 @@ impl NumbatApp {
         let font_size = self.config.ui.font_size;
 
         let mut delete_index = None;
+        let mut retract_index = None;
         let mut clear_all = false;
         let mut reuse: Option<String> = None;
         let mut copy: Option<String> = None;
+        let mut convert: Option<String> = None;
+        let mut toggle_units = None;
+        let mut export: Option<String> = None;
+        let mut watch: Option<String> = None;
+        let mut edit = None;
+        let mut edit_action = EntryAction::None;
+        let names = self.session.names();
+        let reactive = self.config.input.recompute_dependents;
 
         ScrollArea::vertical()
             .auto_shrink([false, false])
@@ -52,14 +77,33 @@ impl NumbatApp {
                             self.empty_state(ui);
                         }
 
+                        let count = self.session.entries.len();
                         for (index, entry) in self.session.entries.iter().enumerate() {
                             ui.push_id(index, |ui| {
-                                match entry_card(ui, entry, &palette, font_size) {
+                                if let Some((_, draft)) =
+                                    self.editing.as_mut().filter(|(i, _)| *i == index)
+                                {
+                                    edit_action = edit_card(ui, draft, &names, &palette, font_size);
+                                    return;
+                                }
+                                let latest = index + 1 == count;
+                                match entry_card(ui, entry, &names, &palette, font_size, latest) {
                                     EntryAction::None => {}
                                     EntryAction::Delete => delete_index = Some(index),
+                                    EntryAction::Retract => retract_index = Some(index),
                                     EntryAction::ClearAll => clear_all = true,
                                     EntryAction::Reuse(text) => reuse = Some(text),
                                     EntryAction::CopyResult(text) => copy = Some(text),
+                                    EntryAction::TogglePreferredUnits => {
+                                        toggle_units = Some(index);
+                                    }
+                                    EntryAction::ExportCsv(csv) => export = Some(csv),
+                                    EntryAction::Watch(expression) => watch = Some(expression),
+                                    EntryAction::Edit => edit = Some(index),
+                                    EntryAction::CommitEdit { .. } | EntryAction::CancelEdit => {}
+                                    EntryAction::Convert(unit) => {
+                                        convert = Some(with_conversion(&entry.input, &unit));
+                                    }
                                 }
                             });
                         }
@@ -73,10 +117,32 @@ impl NumbatApp {
                 }
             });
 
+        match edit_action {
+            EntryAction::CommitEdit { replay_all } => {
+                let replay = match (replay_all, reactive) {
+                    (true, _) => Replay::All,
+                    (false, true) => Replay::Dependents,
+                    (false, false) => Replay::Edited,
+                };
+                if let Some((index, line)) = self.editing.take() {
+                    self.session.edit_entry(index, &line, replay);
+                }
+            }
+            EntryAction::CancelEdit => self.editing = None,
+            _ => {}
+        }
+        if let Some(index) = edit {
+            self.editing = Some((index, self.session.entries[index].input.clone()));
+        }
         if clear_all {
+            self.editing = None;
             self.session.clear();
         } else if let Some(index) = delete_index {
+            self.editing = None;
             self.session.delete_entry(index);
+        } else if let Some(index) = retract_index {
+            self.editing = None;
+            self.session.retract_entry(index);
         }
         if let Some(text) = reuse {
             self.session.input = text;
@@ -87,6 +153,19 @@ impl NumbatApp {
         if let Some(text) = copy {
             self.copy_to_clipboard(ui.ctx(), text);
         }
+        if let Some(csv) = export {
+            self.export_file(ui.ctx(), "numbat-sweep", "csv", csv.as_bytes());
+        }
+        if let Some(expression) = watch {
+            self.add_watch(&expression);
+        }
+        if let Some(index) = toggle_units {
+            self.session.entries[index].toggle_preferred_units();
+        }
+        if let Some(line) = convert {
+            // Evaluate it as a new entry, keeping whatever is being typed.
+            self.session.submit_line(line);
+        }
     }
 
     fn empty_state(&self, ui: &mut egui::Ui) {
@@ -131,6 +210,26 @@ impl NumbatApp {
                     ui.add_space(side);
                     ui.vertical(|ui| {
                         ui.set_max_width(inner_width);
+                        let input_id = self.main_input_id();
+
+                        if self.show_symbols {
+                            if let Some(symbol) = symbols::symbol_palette(ui, &palette, font_size) {
+                                insert_at_cursor(
+                                    ui.ctx(),
+                                    input_id,
+                                    &mut self.session.input,
+                                    symbol,
+                                );
+                                self.session.on_input_edited();
+                                self.completion.close();
+                            }
+                            ui.add_space(6.0);
+                        }
+
+                        if !self.session.checkpoints.is_empty() {
+                            self.checkpoint_strip(ui);
+                            ui.add_space(6.0);
+                        }
 
                         // Live preview of the value while typing. The row has
                         // a fixed height so the bar never jumps while typing.
@@ -167,7 +266,6 @@ impl NumbatApp {
                         );
                         ui.add_space(4.0);
 
-                        let input_id = self.main_input_id();
                         ui.horizontal(|ui| {
                             ui.label(
                                 RichText::new("❯")
@@ -188,16 +286,81 @@ impl NumbatApp {
                                     .frame(false)
                                 };
 
-                                if ui.add(icon_button("⚙")).on_hover_text("Settings").clicked() {
-                                    self.open_settings();
-                                }
-                                if ui
-                                    .add(icon_button("🗑"))
-                                    .on_hover_text("Clear history")
-                                    .clicked()
-                                {
-                                    self.session.clear();
-                                }
+                                let symbols_button = egui::Button::new(
+                                    RichText::new("Ω").size(17.0).color(if self.show_symbols {
+                                        palette.accent
+                                    } else {
+                                        palette.text_dim
+                                    }),
+                                )
+                                .frame(false);
+
+                                let inspector_button =
+                                    egui::Button::new(RichText::new("🔍").size(15.0).color(
+                                        if self.show_inspector {
+                                            palette.accent
+                                        } else {
+                                            palette.text_dim
+                                        },
+                                    ))
+                                    .frame(false);
+
+                                let browser_button = egui::Button::new(
+                                    RichText::new("📖").size(15.0).color(if self.show_browser {
+                                        palette.accent
+                                    } else {
+                                        palette.text_dim
+                                    }),
+                                )
+                                .frame(false);
+
+                                let watch_button = egui::Button::new(
+                                    RichText::new("👁").size(15.0).color(if self.show_watches {
+                                        palette.accent
+                                    } else {
+                                        palette.text_dim
+                                    }),
+                                )
+                                .frame(false);
+
+                                let plot_button = egui::Button::new(
+                                    RichText::new("📈").size(16.0).color(if self.show_plot {
+                                        palette.accent
+                                    } else {
+                                        palette.text_dim
+                                    }),
+                                )
+                                .frame(false);
+
+                                // The buttons run registry actions; tooltips
+                                // show their shortcuts.
+                                let ctx = ui.ctx().clone();
+                                let keybindings = self.config.keybindings.clone();
+                                let hint = |action: &Action| match actions::shortcut_label(
+                                    &ctx,
+                                    &keybindings,
+                                    action,
+                                ) {
+                                    Some(shortcut) => {
+                                        format!("{}  ({shortcut})", action.title())
+                                    }
+                                    None => action.title(),
+                                };
+                                let mut action_button =
+                                    |ui: &mut egui::Ui, button: egui::Button, action: Action| {
+                                        if ui.add(button).on_hover_text(hint(&action)).clicked() {
+                                            self.run_action(&ctx, action);
+                                        }
+                                    };
+
+                                action_button(ui, icon_button("☰"), Action::CommandPalette);
+                                action_button(ui, icon_button("⚙"), Action::OpenSettings);
+                                action_button(ui, icon_button("🗑"), Action::ClearHistory);
+                                action_button(ui, symbols_button, Action::ToggleSymbols);
+                                action_button(ui, plot_button, Action::TogglePlot);
+                                action_button(ui, inspector_button, Action::ToggleInspector);
+                                action_button(ui, browser_button, Action::ToggleBrowser);
+                                action_button(ui, watch_button, Action::ToggleWatches);
 
                                 ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
                                     let result = InputField {
@@ -207,6 +370,8 @@ impl NumbatApp {
                                         font_size: font_size + 2.0,
                                         hint: "Calculate…",
                                         id: input_id,
+                                        options: &self.config.input,
+                                        kill_ring: &mut self.kill_ring,
                                     }
                                     .show(ui);
 
@@ -228,6 +393,47 @@ impl NumbatApp {
             });
     }
 
+    /// The saved checkpoints, latest last; clicking one rolls back to it.
+    fn checkpoint_strip(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        let mut chosen = None;
+        let mut dropped = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label(
+                RichText::new("Checkpoints")
+                    .size(11.5)
+                    .color(palette.text_faint),
+            );
+            for checkpoint in &self.session.checkpoints {
+                let chip = ui
+                    .add(
+                        egui::Button::new(
+                            RichText::new(format!("⚑ {}", checkpoint.name))
+                                .size(11.5)
+                                .color(palette.text_dim),
+                        )
+                        .small(),
+                    )
+                    .on_hover_text("Restore: roll definitions and history back to here");
+                if chip.clicked() {
+                    chosen = Some(Action::RestoreCheckpoint(checkpoint.name.clone()));
+                }
+                chip.context_menu(|ui| {
+                    if ui.button("🗙 Delete checkpoint").clicked() {
+                        dropped = Some(checkpoint.name.clone());
+                        ui.close();
+                    }
+                });
+            }
+        });
+        if let Some(action) = chosen {
+            self.run_action(ui.ctx(), action);
+        }
+        if let Some(name) = dropped {
+            self.session.drop_checkpoint(&name);
+        }
+    }
+
     pub fn main_input_id(&self) -> egui::Id {
         egui::Id::new("main_input")
     }
@@ -1,47 +1,103 @@
 //! UI building blocks shared by the main window and the quick panel.
 
+pub mod browser;
+pub mod cheat_sheet;
+pub mod command_palette;
+pub mod inspector;
 pub mod main_window;
+pub mod plot;
 pub mod quick_panel;
+pub mod readline;
+pub mod scrub;
 pub mod settings;
+pub mod symbols;
+pub mod watch;
+
+use std::sync::Arc;
 
 use egui::text::{CCursor, CCursorRange};
 use egui::text_edit::TextEditState;
 use egui::{Color32, CornerRadius, FontFamily, FontId, Frame, Margin, RichText, Stroke};
 
+use crate::actions::Action;
+use crate::config::{InputConfig, Keymap};
+use crate::engine::Sweep;
 use crate::session::{HistoryEntry, Session};
+use crate::syntax::{self, Names};
 use crate::theme::{highlight_input, markup_job, Palette};
 
 // ---------------------------------------------------------------------------
 // Toasts
 
-/// Small transient notifications ("Copied to clipboard").
+/// Small transient notifications ("Copied to clipboard"), some with a
+/// button ("Undo").
 #[derive(Default)]
 pub struct Toasts {
-    items: Vec<(String, f64)>,
+    items: Vec<Toast>,
+}
+
+struct Toast {
+    text: String,
+    expires: f64,
+    /// The button's label and the action it runs.
+    button: Option<(&'static str, Action)>,
 }
 
 impl Toasts {
     pub fn push(&mut self, ctx: &egui::Context, text: impl Into<String>) {
         let now = ctx.input(|i| i.time);
-        self.items.push((text.into(), now + 1.6));
+        self.items.push(Toast {
+            text: text.into(),
+            expires: now + 1.6,
+            button: None,
+        });
     }
 
-    /// Draws the toasts near the bottom of the current viewport.
-    pub fn ui(&mut self, ctx: &egui::Context, palette: &Palette, id_salt: &str) {
+    /// Pushes a toast with a button running `action`; it stays longer, to
+    /// leave time to click. It replaces the toasts with a button shown so
+    /// far, whose actions may no longer make sense.
+    pub fn push_with_button(
+        &mut self,
+        ctx: &egui::Context,
+        text: impl Into<String>,
+        label: &'static str,
+        action: Action,
+    ) {
         let now = ctx.input(|i| i.time);
-        self.items.retain(|(_, expires)| *expires > now);
+        self.items.retain(|toast| toast.button.is_none());
+        self.items.push(Toast {
+            text: text.into(),
+            expires: now + 6.0,
+            button: Some((label, action)),
+        });
+    }
+
+    /// Drops the toasts whose button's action `keep` rejects.
+    pub fn retain_buttons(&mut self, keep: impl Fn(&Action) -> bool) {
+        self.items.retain(|toast| match &toast.button {
+            Some((_, action)) => keep(action),
+            None => true,
+        });
+    }
+
+    /// Draws the toasts near the bottom of the current viewport, returning
+    /// the action of a clicked button.
+    pub fn ui(&mut self, ctx: &egui::Context, palette: &Palette, id_salt: &str) -> Option<Action> {
+        let now = ctx.input(|i| i.time);
+        self.items.retain(|toast| toast.expires > now);
         if self.items.is_empty() {
-            return;
+            return None;
         }
         ctx.request_repaint(); // keep animating until they expire
 
         let screen = ctx.content_rect();
-        egui::Area::new(egui::Id::new(("toasts", id_salt)))
+        let clicked = egui::Area::new(egui::Id::new(("toasts", id_salt)))
             .order(egui::Order::Foreground)
             .fixed_pos(egui::pos2(screen.center().x, screen.bottom() - 64.0))
             .pivot(egui::Align2::CENTER_BOTTOM)
             .show(ctx, |ui| {
-                for (text, _) in &self.items {
+                let mut clicked = None;
+                for (index, toast) in self.items.iter().enumerate() {
                     Frame::new()
                         .fill(if palette.dark {
                             Color32::from_rgb(0x2c, 0x31, 0x3d)
@@ -57,10 +113,32 @@ impl Toasts {
                             color: Color32::from_black_alpha(90),
                         })
                         .show(ui, |ui| {
-                            ui.label(RichText::new(text).color(Color32::WHITE).size(13.0));
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new(&toast.text).color(Color32::WHITE).size(13.0),
+                                );
+                                let Some((label, action)) = &toast.button else {
+                                    return;
+                                };
+                                let button = egui::Button::new(
+                                    RichText::new(*label)
+                                        .color(palette.accent)
+                                        .size(13.0)
+                                        .strong(),
+                                )
+                                .frame(false);
+                                if ui.add(button).clicked() {
+                                    clicked = Some((index, action.clone()));
+                                }
+                            });
                         });
                 }
-            });
+                clicked
+            })
+            .inner;
+        let (index, action) = clicked?;
+        self.items.remove(index);
+        Some(action)
     }
 }
 
@@ -91,8 +169,17 @@ impl CompletionState {
     }
 }
 
+/// Characters of a completable word: exactly those of a numbat
+/// identifier, so superscripts (`m²`) and other symbols end a word.
 fn is_word_char(c: char) -> bool {
-    c.is_alphanumeric() || c == '_' || c == '°'
+    syntax::is_identifier_start(c) || syntax::is_identifier_continue(c)
+}
+
+/// Byte offset of the `index`th character (or the end of the text).
+fn char_to_byte(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map_or(text.len(), |(i, _)| i)
 }
 
 fn longest_common_prefix(items: &[String]) -> String {
@@ -131,22 +218,46 @@ pub struct InputField<'a> {
     pub font_size: f32,
     pub hint: &'a str,
     pub id: egui::Id,
+    pub options: &'a InputConfig,
+    pub kill_ring: &'a mut readline::KillRing,
 }
 
 impl InputField<'_> {
     pub fn show(mut self, ui: &mut egui::Ui) -> InputFieldResult {
         let id = self.id;
         let had_focus = ui.ctx().memory(|m| m.has_focus(id));
-        let mut set_cursor_to_end = false;
+        let mut move_cursor: Option<CCursorRange> = None;
 
         if had_focus {
-            self.handle_keys(ui, &mut set_cursor_to_end);
+            self.handle_keys(ui, &mut move_cursor);
+        }
+
+        // Applied before the text edit runs, so it already works with the
+        // new cursor this frame.
+        if let Some(range) = move_cursor {
+            let mut state = TextEditState::load(ui.ctx(), id).unwrap_or_default();
+            state.cursor.set_char_range(Some(range));
+            state.store(ui.ctx(), id);
         }
 
+        // The cursor (as a byte offset) for marking the bracket pair next
+        // to it; only while the field is focused.
+        let cursor_of = |ctx: &egui::Context, text: &str| {
+            TextEditState::load(ctx, id)
+                .and_then(|s| s.cursor.char_range())
+                .map(|r| char_to_byte(text, r.primary.index))
+        };
+        let cursor = if had_focus {
+            cursor_of(ui.ctx(), &self.session.input)
+        } else {
+            None
+        };
+
         let palette = *self.palette;
         let font_size = self.font_size;
+        let names = self.session.names();
         let mut layouter = move |ui: &egui::Ui, buf: &dyn egui::TextBuffer, wrap_width: f32| {
-            let mut job = highlight_input(buf.as_str(), &palette, font_size);
+            let mut job = highlight_input(buf.as_str(), cursor, &names, &palette, font_size);
             job.wrap.max_width = wrap_width;
             ui.fonts_mut(|f| f.layout_job(job))
         };
@@ -162,6 +273,7 @@ impl InputField<'_> {
             .lock_focus(true) // Tab is ours (completion)
             .layouter(&mut layouter)
             .show(ui);
+        self.scrub(ui, &output);
         let response = output.response.response;
 
         if self.session.input != before_edit {
@@ -170,11 +282,10 @@ impl InputField<'_> {
             self.completion.close();
         }
 
-        if set_cursor_to_end {
-            let mut state = TextEditState::load(ui.ctx(), id).unwrap_or_default();
-            let end = CCursor::new(self.session.input.chars().count());
-            state.cursor.set_char_range(Some(CCursorRange::one(end)));
-            state.store(ui.ctx(), id);
+        // The text was laid out with the cursor from before this frame's
+        // edits; lay it out again when the cursor moved.
+        if had_focus && cursor_of(ui.ctx(), &self.session.input) != cursor {
+            ui.ctx().request_repaint();
         }
 
         let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
@@ -187,11 +298,112 @@ impl InputField<'_> {
         }
     }
 
-    fn handle_keys(&mut self, ui: &mut egui::Ui, set_cursor_to_end: &mut bool) {
-        // Tab: complete the word before the cursor.
+    /// Scrubbing of number literals: scrolling over one, or dragging it
+    /// with Alt held, steps it by its last digit.
+    fn scrub(&mut self, ui: &egui::Ui, output: &egui::text_edit::TextEditOutput) {
+        let drag_id = self.id.with("scrub_drag");
+        let scroll_id = self.id.with("scrub_scroll");
+        let response = &output.response.response;
+        let input = &self.session.input;
+        let hovered = response.hover_pos().and_then(|pos| {
+            let cursor = output.galley.cursor_from_pos(pos - output.galley_pos);
+            scrub::literal_at(input, char_to_byte(input, cursor.index))
+        });
+
+        let mut drag: Option<scrub::Drag> = ui.ctx().data(|d| d.get_temp(drag_id));
+        let alt = ui.input(|i| i.modifiers.alt);
+        if drag.is_none() && alt && response.drag_started() {
+            if let (Some(range), Some(pos)) = (hovered.clone(), ui.ctx().pointer_interact_pos()) {
+                drag = Some(scrub::Drag {
+                    start: range.start,
+                    original: input[range.clone()].to_owned(),
+                    len: range.len(),
+                    origin_x: pos.x,
+                });
+            }
+        }
+
+        let mut edit: Option<(std::ops::Range<usize>, String)> = None;
+        if let Some(mut state) = drag.take() {
+            if response.dragged() {
+                let x = ui
+                    .ctx()
+                    .pointer_interact_pos()
+                    .map_or(state.origin_x, |p| p.x);
+                let steps = ((x - state.origin_x) / scrub::DRAG_STEP) as i64;
+                if let Some(literal) = scrub::step(&state.original, steps) {
+                    edit = Some((state.start..state.start + state.len, literal.clone()));
+                    state.len = literal.len();
+                }
+                ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+                drag = Some(state);
+            }
+        } else if let Some(range) = hovered {
+            let delta = ui.input_mut(|i| std::mem::take(&mut i.smooth_scroll_delta.y));
+            let scrolled = ui
+                .ctx()
+                .data(|d| d.get_temp::<f32>(scroll_id))
+                .unwrap_or(0.0)
+                + delta;
+            let steps = (scrolled / scrub::SCROLL_STEP) as i64;
+            ui.ctx().data_mut(|d| {
+                d.insert_temp(scroll_id, scrolled - steps as f32 * scrub::SCROLL_STEP)
+            });
+            if steps != 0 {
+                if let Some(literal) = scrub::step(&input[range.clone()], steps) {
+                    edit = Some((range, literal));
+                }
+            }
+            if alt {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+            }
+        }
+        ui.ctx().data_mut(|d| match drag {
+            Some(drag) => {
+                d.insert_temp(drag_id, drag);
+            }
+            None => d.remove::<scrub::Drag>(drag_id),
+        });
+
+        if let Some((range, literal)) = edit {
+            if self.session.input[range.clone()] != literal {
+                let end = range.start + literal.chars().count();
+                self.session.input.replace_range(range, &literal);
+                // Keep the cursor off the selection a drag may have started.
+                let mut state = output.state.clone();
+                state
+                    .cursor
+                    .set_char_range(Some(CCursorRange::one(CCursor::new(
+                        self.session.input[..end].chars().count(),
+                    ))));
+                state.store(ui.ctx(), self.id);
+                ui.ctx().request_repaint();
+            }
+        }
+    }
+
+    fn handle_keys(&mut self, ui: &mut egui::Ui, move_cursor: &mut Option<CCursorRange>) {
+        let end_of_input = |session: &Session| {
+            Some(CCursorRange::one(CCursor::new(
+                session.input.chars().count(),
+            )))
+        };
+
+        if self.options.keymap == Keymap::Emacs && self.readline_keys(ui, move_cursor) {
+            return;
+        }
+
+        // Tab: expand a `\name` escape or `^2` exponent before the cursor,
+        // otherwise complete the word before it.
         if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+            if !self.completion.is_open() {
+                if let Some(cursor) = self.expand_symbol(ui) {
+                    *move_cursor = Some(CCursorRange::one(CCursor::new(cursor)));
+                    return;
+                }
+            }
             self.complete(ui);
-            *set_cursor_to_end = true;
+            *move_cursor = end_of_input(self.session);
             return;
         }
 
@@ -209,14 +421,216 @@ impl InputField<'_> {
             && self.session.navigate_up()
         {
             self.completion.close();
-            *set_cursor_to_end = true;
+            *move_cursor = end_of_input(self.session);
         }
         if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown))
             && self.session.navigate_down()
         {
             self.completion.close();
-            *set_cursor_to_end = true;
+            *move_cursor = end_of_input(self.session);
+        }
+
+        let paired = self.options.auto_close_brackets && self.pair_brackets(ui, move_cursor);
+        if !paired && self.options.symbol_substitutions {
+            self.substitute_ligature(ui, move_cursor);
+        }
+    }
+
+    /// The readline keymap. Returns whether a bound key was pressed.
+    fn readline_keys(&mut self, ui: &mut egui::Ui, move_cursor: &mut Option<CCursorRange>) -> bool {
+        let command = ui.input_mut(|i| {
+            let (index, command) = i.events.iter().enumerate().find_map(|(index, e)| match e {
+                egui::Event::Key {
+                    key,
+                    modifiers,
+                    pressed: true,
+                    ..
+                } => readline::command_for(*modifiers, *key).map(|c| (index, c)),
+                _ => None,
+            })?;
+            i.events.remove(index);
+            // Option+letter also types a character on macOS (Option+F is ƒ).
+            i.events.retain(|e| !matches!(e, egui::Event::Text(_)));
+            Some(command)
+        });
+        let Some(command) = command else {
+            return false;
+        };
+
+        match command {
+            readline::Command::HistoryPrevious => {
+                if self.session.navigate_up() {
+                    self.completion.close();
+                    *move_cursor = Some(CCursorRange::one(CCursor::new(
+                        self.session.input.chars().count(),
+                    )));
+                }
+            }
+            readline::Command::HistoryNext => {
+                if self.session.navigate_down() {
+                    self.completion.close();
+                    *move_cursor = Some(CCursorRange::one(CCursor::new(
+                        self.session.input.chars().count(),
+                    )));
+                }
+            }
+            _ => {
+                let cursor = TextEditState::load(ui.ctx(), self.id)
+                    .and_then(|s| s.cursor.char_range())
+                    .map_or(self.session.input.chars().count(), |r| r.primary.index);
+                let before = self.session.input.clone();
+                let cursor = self
+                    .kill_ring
+                    .apply(command, &mut self.session.input, cursor);
+                *move_cursor = Some(CCursorRange::one(CCursor::new(cursor)));
+                if self.session.input != before {
+                    self.on_smart_edit();
+                }
+            }
         }
+        true
+    }
+
+    /// The selection as a sorted character range, if the field has one.
+    fn selection(&self, ctx: &egui::Context) -> Option<std::ops::Range<usize>> {
+        TextEditState::load(ctx, self.id)
+            .and_then(|s| s.cursor.char_range())
+            .map(|r| r.as_sorted_char_range())
+            .filter(|r| r.end <= self.session.input.chars().count())
+    }
+
+    /// Replaces the escape or exponent ending at the cursor with its
+    /// symbol. Returns the new cursor position.
+    fn expand_symbol(&mut self, ui: &egui::Ui) -> Option<usize> {
+        let selection = self.selection(ui.ctx()).filter(|s| s.is_empty())?;
+        let cursor = char_to_byte(&self.session.input, selection.start);
+        let (start, replacement) = symbols::expand_before_cursor(&self.session.input[..cursor])?;
+        self.session
+            .input
+            .replace_range(start..cursor, &replacement);
+        self.on_smart_edit();
+        Some(self.session.input[..start].chars().count() + replacement.chars().count())
+    }
+
+    /// ASCII ligatures: typing the last character of `->`, `<=`, `>=` or
+    /// `!=` replaces the pair with its symbol, except inside strings.
+    fn substitute_ligature(&mut self, ui: &mut egui::Ui, move_cursor: &mut Option<CCursorRange>) {
+        let Some(selection) = self.selection(ui.ctx()).filter(|s| s.is_empty()) else {
+            return;
+        };
+        let Some((typed, c)) = typed_char(ui) else {
+            return;
+        };
+        let cursor = char_to_byte(&self.session.input, selection.start);
+        let before = &self.session.input[..cursor];
+        if before.matches('"').count() % 2 == 1 {
+            return;
+        }
+        let Some((take_back, symbol)) = symbols::ligature(before, c) else {
+            return;
+        };
+        let start = selection.start - take_back;
+        let start_byte = char_to_byte(&self.session.input, start);
+        self.session.input.replace_range(start_byte..cursor, symbol);
+        *move_cursor = Some(CCursorRange::one(CCursor::new(
+            start + symbol.chars().count(),
+        )));
+        consume_typed(ui, &typed);
+        self.on_smart_edit();
+    }
+
+    /// Bracket and quote pairing: typing an opening bracket inserts its
+    /// partner (or wraps the selection), typing a closing one right in
+    /// front of the same character steps over it, and Backspace inside an
+    /// empty pair removes both halves. Returns whether it handled the key.
+    fn pair_brackets(&mut self, ui: &mut egui::Ui, move_cursor: &mut Option<CCursorRange>) -> bool {
+        let Some(selection) = self.selection(ui.ctx()) else {
+            return false;
+        };
+        let chars: Vec<char> = self.session.input.chars().collect();
+        let before = selection.start.checked_sub(1).map(|i| chars[i]);
+        let after = chars.get(selection.end).copied();
+        let byte = |i: usize| char_to_byte(&self.session.input, i);
+        // The pair inserted last (its opening character's index, and the
+        // text right after), while the text has not changed since.
+        let auto_pair_id = self.id.with("auto_pair");
+        let auto_pair = ui
+            .ctx()
+            .data(|d| d.get_temp::<(usize, String)>(auto_pair_id))
+            .filter(|(_, text)| *text == self.session.input)
+            .map(|(at, _)| at);
+
+        if selection.is_empty()
+            && auto_pair.is_some_and(|at| at + 1 == selection.start)
+            && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Backspace))
+        {
+            let range = byte(selection.start - 1)..byte(selection.end + 1);
+            self.session.input.replace_range(range, "");
+            *move_cursor = Some(CCursorRange::one(CCursor::new(selection.start - 1)));
+            self.on_smart_edit();
+            return true;
+        }
+
+        let Some((typed, c)) = typed_char(ui) else {
+            return false;
+        };
+
+        let inside_string = chars[..selection.start]
+            .iter()
+            .filter(|&&c| c == '"')
+            .count()
+            % 2
+            == 1;
+        let handled =
+            if selection.is_empty() && matches!(c, ')' | ']' | '}' | '"') && after == Some(c) {
+                // Step over the closing character.
+                *move_cursor = Some(CCursorRange::one(CCursor::new(selection.end + 1)));
+                true
+            } else if let Some(close) = syntax::closing_pair(c) {
+                if !selection.is_empty() {
+                    // Wrap the selection.
+                    let (start, end) = (byte(selection.start), byte(selection.end));
+                    self.session.input.insert(end, close);
+                    self.session.input.insert(start, c);
+                    *move_cursor = Some(CCursorRange::two(
+                        CCursor::new(selection.start + 1),
+                        CCursor::new(selection.end + 1),
+                    ));
+                    true
+                } else {
+                    // Only pair up in front of whitespace or a closing
+                    // bracket, never glued to a word; quotes not inside a string.
+                    let free_after = after
+                        .is_none_or(|a| a.is_whitespace() || matches!(a, ')' | ']' | '}' | ','));
+                    let free_before =
+                        c != '"' || !(inside_string || before.is_some_and(is_word_char));
+                    if free_after && free_before {
+                        let at = byte(selection.start);
+                        self.session.input.insert(at, close);
+                        self.session.input.insert(at, c);
+                        let pair = (selection.start, self.session.input.clone());
+                        ui.ctx().data_mut(|d| d.insert_temp(auto_pair_id, pair));
+                        *move_cursor = Some(CCursorRange::one(CCursor::new(selection.start + 1)));
+                        true
+                    } else {
+                        false
+                    }
+                }
+            } else {
+                false
+            };
+
+        if handled {
+            consume_typed(ui, &typed);
+            self.on_smart_edit();
+        }
+        handled
+    }
+
+    /// Edits made on the user's behalf count as typing.
+    fn on_smart_edit(&mut self) {
+        self.session.on_input_edited();
+        self.completion.close();
     }
 
     /// Tab handler: first press completes to the longest common prefix and
@@ -236,11 +650,7 @@ impl InputField<'_> {
         // Find the word ending at the cursor (or at the end of the text).
         let cursor_byte = TextEditState::load(ui.ctx(), self.id)
             .and_then(|s| s.cursor.char_range())
-            .map(|r| {
-                text.char_indices()
-                    .nth(r.primary.index)
-                    .map_or(text.len(), |(i, _)| i)
-            })
+            .map(|r| char_to_byte(&text, r.primary.index))
             .unwrap_or(text.len());
         let word_start = text[..cursor_byte]
             .char_indices()
@@ -355,23 +765,211 @@ impl InputField<'_> {
     }
 }
 
+/// The character typed this frame, if it is a single one. Only the first
+/// text event of a frame is considered.
+fn typed_char(ui: &egui::Ui) -> Option<(String, char)> {
+    let typed = ui.input(|i| {
+        i.events.iter().find_map(|e| match e {
+            egui::Event::Text(text) => Some(text.clone()),
+            _ => None,
+        })
+    })?;
+    let mut chars = typed.chars();
+    let c = chars.next()?;
+    chars.next().is_none().then_some((typed, c))
+}
+
+/// Removes a text event handled by a smart edit, so the text edit doesn't
+/// insert it as well.
+fn consume_typed(ui: &mut egui::Ui, typed: &str) {
+    ui.input_mut(|i| {
+        if let Some(pos) = i
+            .events
+            .iter()
+            .position(|e| matches!(e, egui::Event::Text(t) if t == typed))
+        {
+            i.events.remove(pos);
+        }
+    });
+}
+
+/// Inserts `insert` at the cursor of the text edit `id` (replacing its
+/// selection, or appending when it has no cursor yet) and puts the cursor
+/// after it. For buttons that type into a field, like the symbol palette.
+pub fn insert_at_cursor(ctx: &egui::Context, id: egui::Id, text: &mut String, insert: &str) {
+    let mut state = TextEditState::load(ctx, id).unwrap_or_default();
+    let len = text.chars().count();
+    let range = state
+        .cursor
+        .char_range()
+        .map(|r| r.as_sorted_char_range())
+        .filter(|r| r.end <= len)
+        .unwrap_or(len..len);
+    let (start, end) = (
+        char_to_byte(text, range.start),
+        char_to_byte(text, range.end),
+    );
+    text.replace_range(start..end, insert);
+    state
+        .cursor
+        .set_char_range(Some(CCursorRange::one(CCursor::new(
+            range.start + insert.chars().count(),
+        ))));
+    state.store(ctx, id);
+    ctx.memory_mut(|m| m.request_focus(id));
+}
+
 // ---------------------------------------------------------------------------
 // History entry cards
 
 pub enum EntryAction {
     None,
     Delete,
+    /// Delete the entry and forget what it defined.
+    Retract,
     Reuse(String),
     CopyResult(String),
+    /// Evaluate the entry again, converted to the given unit.
+    Convert(String),
+    /// Swap between the preferred units and the unit as computed.
+    TogglePreferredUnits,
+    /// Save the sweep table (as CSV) to a file.
+    ExportCsv(String),
+    /// Pin the expression to the watch panel.
+    Watch(String),
+    /// Start editing the entry's input in place.
+    Edit,
+    /// Evaluate the edited input in place of the entry, optionally
+    /// replaying all later entries.
+    CommitEdit {
+        replay_all: bool,
+    },
+    CancelEdit,
     ClearAll,
 }
 
-/// One evaluated line, rendered as a card. Action buttons appear on hover.
+/// The card of an entry whose input is being edited in place.
+pub fn edit_card(
+    ui: &mut egui::Ui,
+    text: &mut String,
+    names: &Arc<Names>,
+    palette: &Palette,
+    font_size: f32,
+) -> EntryAction {
+    let mut action = EntryAction::None;
+    Frame::new()
+        .fill(palette.card)
+        .stroke(Stroke::new(1.5, palette.accent))
+        .corner_radius(CornerRadius::same(12))
+        .inner_margin(Margin::symmetric(16, 12))
+        .show(ui, |ui| {
+            ui.set_min_width(ui.available_width());
+            let names = Arc::clone(names);
+            let palette = *palette;
+            let mut layouter = move |ui: &egui::Ui, buf: &dyn egui::TextBuffer, wrap_width: f32| {
+                let mut job = highlight_input(buf.as_str(), None, &names, &palette, font_size);
+                job.wrap.max_width = wrap_width;
+                ui.fonts_mut(|f| f.layout_job(job))
+            };
+            let response = ui.add(
+                egui::TextEdit::singleline(text)
+                    .font(FontId::new(font_size, FontFamily::Monospace))
+                    .frame(Frame::NONE)
+                    .margin(Margin::ZERO)
+                    .desired_width(f32::INFINITY)
+                    .layouter(&mut layouter),
+            );
+            // Focus it when it appears.
+            if !response.has_focus() && !response.lost_focus() {
+                response.request_focus();
+            }
+            if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                action = EntryAction::CancelEdit;
+            } else if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                action = EntryAction::CommitEdit {
+                    replay_all: ui.input(|i| i.modifiers.shift),
+                };
+            } else if response.lost_focus() {
+                action = EntryAction::CancelEdit;
+            }
+            ui.label(
+                RichText::new(
+                    "Enter: re-run in place · Shift+Enter: also replay everything after it · Esc: cancel",
+                )
+                    .size(11.0)
+                    .color(palette.text_faint),
+            );
+        });
+    action
+}
+
+/// The rows of a sweep, scrolling beyond `max_height`.
+pub fn sweep_table(
+    ui: &mut egui::Ui,
+    sweep: &Sweep,
+    palette: &Palette,
+    font_size: f32,
+    max_height: f32,
+) {
+    let cell = |text: &str, color| RichText::new(text).monospace().size(font_size).color(color);
+    egui::ScrollArea::vertical()
+        .max_height(max_height)
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            egui::Grid::new("sweep_table")
+                .striped(true)
+                .spacing([24.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label(cell(&sweep.variable, palette.text_faint));
+                    ui.label(cell(&sweep.expression, palette.text_faint));
+                    ui.end_row();
+                    for (value, result) in &sweep.rows {
+                        ui.label(cell(value, palette.value));
+                        match result {
+                            Ok(result) => ui.label(cell(result, palette.text)),
+                            Err(e) => ui.label(cell(e, palette.error)),
+                        };
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+/// Small buttons offering to convert a result to other units. Returns the
+/// unit clicked.
+pub fn conversion_chips(ui: &mut egui::Ui, units: &[String], palette: &Palette) -> Option<String> {
+    let mut clicked = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 6.0;
+        for unit in units {
+            let chip = egui::Button::new(
+                RichText::new(format!("→ {unit}"))
+                    .size(11.5)
+                    .color(palette.text_dim),
+            )
+            .small()
+            .corner_radius(CornerRadius::same(8));
+            if ui
+                .add(chip)
+                .on_hover_text(format!("Convert to {unit}"))
+                .clicked()
+            {
+                clicked = Some(unit.clone());
+            }
+        }
+    });
+    clicked
+}
+
+/// One evaluated line, rendered as a card. Action buttons appear on hover,
+/// as do conversion chips (always shown on the `latest` card).
 pub fn entry_card(
     ui: &mut egui::Ui,
     entry: &HistoryEntry,
+    names: &Names,
     palette: &Palette,
     font_size: f32,
+    latest: bool,
 ) -> EntryAction {
     let mut action = EntryAction::None;
 
@@ -386,7 +984,11 @@ pub fn entry_card(
 
     let frame_response = Frame::new()
         .fill(palette.card)
-        .stroke(Stroke::new(1.0, palette.border))
+        .stroke(if entry.recomputed {
+            Stroke::new(1.5, palette.value)
+        } else {
+            Stroke::new(1.0, palette.border)
+        })
         .corner_radius(CornerRadius::same(12))
         .inner_margin(Margin::symmetric(16, 12))
         .show(ui, |ui| {
@@ -395,7 +997,7 @@ pub fn entry_card(
 
             // Input line, with action buttons appearing on the right on hover.
             ui.horizontal(|ui| {
-                let job = highlight_input(&entry.input, palette, font_size);
+                let job = highlight_input(&entry.input, None, names, palette, font_size);
                 ui.add(egui::Label::new(job).wrap());
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -419,9 +1021,21 @@ pub fn entry_card(
                             action = EntryAction::CopyResult(plain.clone());
                         }
                     }
-                    if ui.add(subtle("↻")).on_hover_text("Edit again").clicked() {
+                    if let Some(sweep) = &entry.sweep {
+                        if ui.add(subtle("📋")).on_hover_text("Copy as CSV").clicked() {
+                            action = EntryAction::CopyResult(sweep.to_csv());
+                        }
+                    }
+                    if ui
+                        .add(subtle("↻"))
+                        .on_hover_text("Edit again as a new entry")
+                        .clicked()
+                    {
                         action = EntryAction::Reuse(entry.input.clone());
                     }
+                    if ui.add(subtle("✎")).on_hover_text("Edit in place").clicked() {
+                        action = EntryAction::Edit;
+                    }
                 });
             });
 
@@ -429,6 +1043,20 @@ pub fn entry_card(
                 ui.add(egui::Label::new(markup_job(printed, palette, font_size)).wrap());
             }
 
+            if let Some(sweep) = &entry.sweep {
+                sweep_table(ui, sweep, palette, font_size, 320.0);
+                let export = egui::Button::new(
+                    RichText::new("⤓ Export CSV")
+                        .size(11.5)
+                        .color(palette.text_dim),
+                )
+                .small()
+                .frame(false);
+                if ui.add(export).clicked() {
+                    action = EntryAction::ExportCsv(sweep.to_csv());
+                }
+            }
+
             if let Some(result) = &entry.result {
                 ui.horizontal(|ui| {
                     ui.label(
@@ -448,6 +1076,41 @@ pub fn entry_card(
                         }
                     }
                 });
+                if let Some((_, other)) = &entry.alternative {
+                    ui.horizontal(|ui| {
+                        ui.spacing_mut().item_spacing.x = 6.0;
+                        let (note, button, tooltip) = if entry.reverted {
+                            (
+                                "as computed",
+                                format!("→ {other}"),
+                                "Show in your preferred units",
+                            )
+                        } else {
+                            ("converted from", format!("↺ {other}"), "Show as computed")
+                        };
+                        ui.label(RichText::new(note).size(11.5).color(palette.text_faint));
+                        let revert = egui::Button::new(
+                            RichText::new(button).size(11.5).color(palette.text_dim),
+                        )
+                        .small()
+                        .frame(false);
+                        if ui.add(revert).on_hover_text(tooltip).clicked() {
+                            action = EntryAction::TogglePreferredUnits;
+                        }
+                    });
+                }
+                if entry.recomputed {
+                    ui.label(
+                        RichText::new("updated — an earlier definition changed")
+                            .size(11.5)
+                            .color(palette.value),
+                    );
+                }
+                if (hovered || latest) && !entry.conversions.is_empty() {
+                    if let Some(unit) = conversion_chips(ui, &entry.conversions, palette) {
+                        action = EntryAction::Convert(unit);
+                    }
+                }
             }
 
             if let Some(error) = &entry.error {
@@ -469,6 +1132,10 @@ pub fn entry_card(
             action = EntryAction::Reuse(entry.input.clone());
             ui.close();
         }
+        if ui.button("✎ Edit in place").clicked() {
+            action = EntryAction::Edit;
+            ui.close();
+        }
         if ui.button("📋 Copy input").clicked() {
             action = EntryAction::CopyResult(entry.input.clone());
             ui.close();
@@ -478,12 +1145,36 @@ pub fn entry_card(
                 action = EntryAction::CopyResult(plain.clone());
                 ui.close();
             }
+            // Definitions have no value to watch.
+            if syntax::dependencies(&entry.input).0.is_empty() && ui.button("👁 Watch").clicked()
+            {
+                action = EntryAction::Watch(entry.input.clone());
+                ui.close();
+            }
+        }
+        if let Some(sweep) = &entry.sweep {
+            if ui.button("📋 Copy as CSV").clicked() {
+                action = EntryAction::CopyResult(sweep.to_csv());
+                ui.close();
+            }
+            if ui.button("⤓ Export CSV…").clicked() {
+                action = EntryAction::ExportCsv(sweep.to_csv());
+                ui.close();
+            }
         }
         ui.separator();
         if ui.button("🗙 Remove from history").clicked() {
             action = EntryAction::Delete;
             ui.close();
         }
+        if ui
+            .button("⊖ Remove and retract")
+            .on_hover_text("Also forget what this entry defined, now and after a restart")
+            .clicked()
+        {
+            action = EntryAction::Retract;
+            ui.close();
+        }
         if ui.button("🗑 Clear all").clicked() {
             action = EntryAction::ClearAll;
             ui.close();
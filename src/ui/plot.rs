@@ -0,0 +1,488 @@
+//! The plot panel next to the history: samples one-argument functions or
+//! expressions in `x` over a range through the engine and draws them, with
+//! the units on the axes, a hover readout and PNG export.
+
+use egui::{Align2, Color32, FontId, Frame, Margin, Pos2, Rect, RichText, Stroke};
+
+use crate::app::{export_path, NumbatApp};
+use crate::engine::Plot;
+use crate::theme::Palette;
+
+/// Points sampled per series.
+const SAMPLES: usize = 200;
+
+const PLOT_HEIGHT: f32 = 240.0;
+
+/// Room for the tick labels left of and below the plot area.
+const AXIS_MARGIN: egui::Vec2 = egui::vec2(52.0, 36.0);
+
+pub struct PlotPanel {
+    /// Function names or expressions in `x`, one per series.
+    pub series: Vec<String>,
+    pub from: String,
+    pub to: String,
+    plot: Option<Result<Plot, String>>,
+    /// The inputs (and history length) the plot was sampled for; any change
+    /// resamples, e.g. after a function was redefined.
+    sampled_for: Option<(Vec<String>, String, String, usize)>,
+    /// Where the plot was drawn last, for cropping the PNG export.
+    plot_rect: Option<Rect>,
+}
+
+impl Default for PlotPanel {
+    fn default() -> Self {
+        Self {
+            series: vec!["sin(x)".to_owned()],
+            from: "0".to_owned(),
+            to: "2 pi".to_owned(),
+            plot: None,
+            sampled_for: None,
+            plot_rect: None,
+        }
+    }
+}
+
+impl NumbatApp {
+    pub fn plot_panel(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        egui::Panel::right("plot_panel")
+            .frame(
+                Frame::new()
+                    .fill(palette.bg_raised)
+                    .stroke(Stroke::new(1.0, palette.border))
+                    .inner_margin(Margin::same(14)),
+            )
+            .resizable(true)
+            .default_size(420.0)
+            .min_size(300.0)
+            .show_separator_line(false)
+            .show_inside(ui, |ui| {
+                ui.spacing_mut().item_spacing.y = 8.0;
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new("PLOT")
+                            .size(11.0)
+                            .strong()
+                            .color(palette.text_faint),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let close = egui::Button::new(
+                            RichText::new("×").size(15.0).color(palette.text_dim),
+                        )
+                        .frame(false);
+                        if ui.add(close).on_hover_text("Close").clicked() {
+                            self.show_plot = false;
+                        }
+                        let export = ui.add_enabled(
+                            self.plot.plot_rect.is_some(),
+                            egui::Button::new("Export PNG"),
+                        );
+                        if export.clicked() {
+                            ui.ctx()
+                                .send_viewport_cmd(egui::ViewportCommand::Screenshot(
+                                    egui::UserData::new("plot"),
+                                ));
+                        }
+                    });
+                });
+
+                self.plot_inputs(ui);
+                self.resample_plot();
+
+                match &self.plot.plot {
+                    Some(Ok(plot)) => {
+                        self.plot.plot_rect = Some(draw_plot(ui, plot, &palette));
+                    }
+                    Some(Err(e)) => {
+                        self.plot.plot_rect = None;
+                        ui.label(RichText::new(e).size(12.0).color(palette.error));
+                    }
+                    None => self.plot.plot_rect = None,
+                }
+            });
+    }
+
+    fn plot_inputs(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        let panel = &mut self.plot;
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("x from").color(palette.text_dim));
+            ui.add_sized([90.0, 20.0], field(&mut panel.from, "0 m"));
+            ui.label(RichText::new("to").color(palette.text_dim));
+            ui.add_sized([90.0, 20.0], field(&mut panel.to, "10 m"));
+        });
+
+        let errors: Vec<Option<String>> = match &panel.plot {
+            Some(Ok(plot)) => plot.series.iter().map(|s| s.clone().err()).collect(),
+            _ => Vec::new(),
+        };
+        let mut remove = None;
+        for (index, series) in panel.series.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                let (dot, _) = ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+                ui.painter()
+                    .circle_filled(dot.center(), 4.0, series_color(&palette, index));
+                ui.add(
+                    field(series, "f or an expression in x")
+                        .desired_width(ui.available_width() - 24.0),
+                );
+                let button =
+                    egui::Button::new(RichText::new("×").color(palette.text_dim)).frame(false);
+                if ui.add(button).on_hover_text("Remove the series").clicked() {
+                    remove = Some(index);
+                }
+            });
+            if let Some(Some(e)) = errors.get(index) {
+                ui.label(RichText::new(e).size(11.5).color(palette.error));
+            }
+        }
+        if let Some(index) = remove {
+            panel.series.remove(index);
+        }
+        let add = egui::Button::new(
+            RichText::new("+ Add series")
+                .size(12.0)
+                .color(palette.accent),
+        )
+        .frame(false);
+        if ui.add(add).clicked() {
+            panel.series.push(String::new());
+        }
+    }
+
+    /// Samples the series again when the inputs or the session changed.
+    fn resample_plot(&mut self) {
+        let panel = &mut self.plot;
+        let key = (
+            panel.series.clone(),
+            panel.from.clone(),
+            panel.to.clone(),
+            self.session.entries.len(),
+        );
+        if panel.sampled_for.as_ref() == Some(&key) {
+            return;
+        }
+        let series: Vec<String> = panel
+            .series
+            .iter()
+            .filter(|s| !s.trim().is_empty())
+            .cloned()
+            .collect();
+        panel.plot =
+            (!series.is_empty() && !panel.from.trim().is_empty() && !panel.to.trim().is_empty())
+                .then(|| {
+                    let mut plot =
+                        self.session
+                            .engine
+                            .plot(&series, &panel.from, &panel.to, SAMPLES)?;
+                    // Blank rows stay in place (and keep the colors of the others).
+                    let mut sampled = plot.series.into_iter();
+                    plot.series = panel
+                        .series
+                        .iter()
+                        .map(|s| match s.trim().is_empty() {
+                            true => Ok(Vec::new()),
+                            false => sampled.next().expect("one per non-blank series"),
+                        })
+                        .collect();
+                    Ok(plot)
+                });
+        panel.sampled_for = Some(key);
+    }
+
+    /// Saves the plot area of a screenshot requested by "Export PNG" into
+    /// the documents folder.
+    pub fn save_plot_screenshot(&mut self, ctx: &egui::Context, raw_input: &egui::RawInput) {
+        for event in &raw_input.events {
+            let egui::Event::Screenshot {
+                image, user_data, ..
+            } = event
+            else {
+                continue;
+            };
+            let is_plot = user_data
+                .data
+                .as_ref()
+                .and_then(|d| d.downcast_ref::<&str>())
+                .is_some_and(|name| *name == "plot");
+            let Some(rect) = self.plot.plot_rect.filter(|_| is_plot) else {
+                continue;
+            };
+            let image = image.region(&rect, Some(ctx.pixels_per_point()));
+            let Some(path) = export_path("numbat-plot", "png") else {
+                self.toasts.push(ctx, "No documents folder to export to");
+                continue;
+            };
+            let pixels: Vec<u8> = image
+                .pixels
+                .iter()
+                .flat_map(|p| [p.r(), p.g(), p.b(), p.a()])
+                .collect();
+            let saved =
+                image::RgbaImage::from_raw(image.width() as u32, image.height() as u32, pixels)
+                    .ok_or_else(|| "Export failed".to_owned())
+                    .and_then(|buffer| {
+                        buffer
+                            .save(&path)
+                            .map_err(|e| format!("Export failed: {e}"))
+                    });
+            match saved {
+                Ok(()) => self
+                    .toasts
+                    .push(ctx, format!("Exported to {}", path.display())),
+                Err(e) => self.toasts.push(ctx, e),
+            }
+        }
+    }
+}
+
+fn field<'t>(text: &'t mut String, hint: &str) -> egui::TextEdit<'t> {
+    egui::TextEdit::singleline(text)
+        .hint_text(hint)
+        .font(egui::TextStyle::Monospace)
+}
+
+fn series_color(palette: &Palette, index: usize) -> Color32 {
+    let colors = [
+        palette.accent,
+        palette.value,
+        palette.unit,
+        palette.keyword,
+        palette.error,
+        palette.string,
+    ];
+    colors[index % colors.len()]
+}
+
+/// Draws the sampled series with axes and gridlines, and the nearest points
+/// under the pointer. Returns the rect covered, labels included.
+fn draw_plot(ui: &mut egui::Ui, plot: &Plot, palette: &Palette) -> Rect {
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), PLOT_HEIGHT),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, palette.bg_raised);
+    let area = Rect::from_min_max(
+        rect.min + egui::vec2(AXIS_MARGIN.x, 18.0),
+        rect.max - egui::vec2(8.0, AXIS_MARGIN.y),
+    );
+    let font = FontId::monospace(10.5);
+
+    let points = || {
+        plot.series
+            .iter()
+            .filter_map(|s| s.as_ref().ok())
+            .flatten()
+            .filter(|[x, y]| x.is_finite() && y.is_finite())
+    };
+    let (mut x_range, mut y_range) = (
+        (f64::INFINITY, f64::NEG_INFINITY),
+        (f64::INFINITY, f64::NEG_INFINITY),
+    );
+    for [x, y] in points() {
+        x_range = (x_range.0.min(*x), x_range.1.max(*x));
+        y_range = (y_range.0.min(*y), y_range.1.max(*y));
+    }
+    if !x_range.0.is_finite() {
+        painter.text(
+            area.center(),
+            Align2::CENTER_CENTER,
+            "Nothing to plot",
+            font,
+            palette.text_faint,
+        );
+        return rect;
+    }
+    let x_range = widen(x_range);
+    let y_range = widen(y_range);
+    let to_screen = |[x, y]: [f64; 2]| {
+        Pos2::new(
+            area.left() + ((x - x_range.0) / (x_range.1 - x_range.0)) as f32 * area.width(),
+            area.bottom() - ((y - y_range.0) / (y_range.1 - y_range.0)) as f32 * area.height(),
+        )
+    };
+
+    // Gridlines with tick labels.
+    let grid = Stroke::new(1.0, palette.border);
+    for x in ticks(x_range, 5) {
+        let pos = to_screen([x, y_range.0]);
+        painter.line_segment(
+            [
+                Pos2::new(pos.x, area.top()),
+                Pos2::new(pos.x, area.bottom()),
+            ],
+            grid,
+        );
+        painter.text(
+            pos + egui::vec2(0.0, 4.0),
+            Align2::CENTER_TOP,
+            tick_label(x),
+            font.clone(),
+            palette.text_dim,
+        );
+    }
+    for y in ticks(y_range, 5) {
+        let pos = to_screen([x_range.0, y]);
+        painter.line_segment(
+            [
+                Pos2::new(area.left(), pos.y),
+                Pos2::new(area.right(), pos.y),
+            ],
+            grid,
+        );
+        painter.text(
+            pos - egui::vec2(6.0, 0.0),
+            Align2::RIGHT_CENTER,
+            tick_label(y),
+            font.clone(),
+            palette.text_dim,
+        );
+    }
+    painter.rect_stroke(
+        area,
+        0.0,
+        Stroke::new(1.0, palette.text_faint),
+        egui::StrokeKind::Middle,
+    );
+    let axis_label = |unit: &str| {
+        if unit.is_empty() {
+            "(no unit)".to_owned()
+        } else {
+            format!("[{unit}]")
+        }
+    };
+    painter.text(
+        Pos2::new(area.center().x, rect.bottom() - 2.0),
+        Align2::CENTER_BOTTOM,
+        format!("x {}", axis_label(&plot.x_unit)),
+        font.clone(),
+        palette.text,
+    );
+    painter.text(
+        Pos2::new(rect.left() + 2.0, rect.top() + 2.0),
+        Align2::LEFT_TOP,
+        format!("y {}", axis_label(&plot.y_unit)),
+        font.clone(),
+        palette.text,
+    );
+
+    // The series, broken where a value is not finite.
+    let clipped = painter.with_clip_rect(area.expand(1.0));
+    for (index, series) in plot.series.iter().enumerate() {
+        let Ok(series) = series else { continue };
+        let stroke = Stroke::new(2.0, series_color(palette, index));
+        for run in series.split(|[x, y]| !x.is_finite() || !y.is_finite()) {
+            clipped.line(run.iter().copied().map(to_screen).collect(), stroke);
+        }
+    }
+
+    // Readout of the nearest sample of each series.
+    let Some(pointer) = response.hover_pos().filter(|p| area.contains(*p)) else {
+        return rect;
+    };
+    let x =
+        x_range.0 + f64::from((pointer.x - area.left()) / area.width()) * (x_range.1 - x_range.0);
+    painter.line_segment(
+        [
+            Pos2::new(pointer.x, area.top()),
+            Pos2::new(pointer.x, area.bottom()),
+        ],
+        Stroke::new(1.0, palette.text_faint),
+    );
+    let nearest: Vec<(usize, [f64; 2])> = plot
+        .series
+        .iter()
+        .enumerate()
+        .filter_map(|(index, series)| {
+            let series = series.as_ref().ok()?;
+            let point = series
+                .iter()
+                .filter(|[_, y]| y.is_finite())
+                .min_by(|a, b| (a[0] - x).abs().total_cmp(&(b[0] - x).abs()))?;
+            Some((index, *point))
+        })
+        .collect();
+    for (index, point) in &nearest {
+        clipped.circle_filled(to_screen(*point), 4.0, series_color(palette, *index));
+    }
+    response.on_hover_ui_at_pointer(|ui| {
+        if let Some((_, [x, _])) = nearest.first() {
+            ui.label(RichText::new(format!("x = {} {}", readout(*x), plot.x_unit)).monospace());
+        }
+        for (index, [_, y]) in &nearest {
+            ui.label(
+                RichText::new(format!("y = {} {}", readout(*y), plot.y_unit))
+                    .monospace()
+                    .color(series_color(palette, *index)),
+            );
+        }
+    });
+    rect
+}
+
+/// Pads a value range by 5% and gives a constant one some height.
+fn widen((min, max): (f64, f64)) -> (f64, f64) {
+    if max - min <= f64::EPSILON * min.abs().max(1.0) {
+        let pad = min.abs().max(1.0) * 0.5;
+        return (min - pad, max + pad);
+    }
+    let pad = (max - min) * 0.05;
+    (min - pad, max + pad)
+}
+
+/// About `count` round tick positions (steps of 1, 2 or 5 × 10ⁿ) within
+/// the range.
+fn ticks((min, max): (f64, f64), count: usize) -> Vec<f64> {
+    let raw = (max - min) / count as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= raw)
+        .unwrap_or(10.0 * magnitude);
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    // Multiplying (rather than accumulating) keeps 0.3 from becoming
+    // 0.30000000000000004.
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+fn tick_label(value: f64) -> String {
+    if value == 0.0 {
+        "0".to_owned()
+    } else if !(1e-3..1e5).contains(&value.abs()) {
+        format!("{value:.1e}")
+    } else {
+        format!("{value:.3}")
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_owned()
+    }
+}
+
+fn readout(value: f64) -> String {
+    if value != 0.0 && !(1e-3..1e6).contains(&value.abs()) {
+        format!("{value:.4e}")
+    } else {
+        format!("{value:.4}")
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_are_round_numbers() {
+        assert_eq!(ticks((0.0, 10.0), 5), vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(ticks((-0.33, 1.05), 5), vec![0.0, 0.5, 1.0]);
+        assert_eq!(ticks((1200.0, 4800.0), 4), vec![2000.0, 3000.0, 4000.0]);
+        assert_eq!(tick_label(0.30000000000000004), "0.3");
+        assert_eq!(tick_label(-0.0), "0");
+        assert_eq!(tick_label(250000.0), "2.5e5");
+    }
+}
@@ -3,17 +3,30 @@
 //! shared with the main window, so anything typed or evaluated here can be
 //! picked up seamlessly in the full window ("Open in window", ⌘/Ctrl+⏎).
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use egui::{
-    Color32, CornerRadius, Frame, Margin, RichText, Stroke, ViewportBuilder, ViewportCommand,
-    ViewportId,
+    Color32, CornerRadius, Frame, Key, Margin, Modifiers, RichText, Stroke, ViewportBuilder,
+    ViewportCommand, ViewportId,
 };
 
+use crate::actions::{self, Action, Scope};
 use crate::app::NumbatApp;
+use crate::config::{QuickPanelEnter, QuickPanelPosition};
+use crate::session::with_conversion;
 use crate::theme::markup_job;
-use crate::ui::InputField;
+use crate::ui::{conversion_chips, sweep_table, InputField};
+
+/// Extra height of the expanded panel, which lists the recent entries.
+pub const EXPANDED_HEIGHT: f32 = 260.0;
 
-pub const PANEL_WIDTH: f32 = 680.0;
-pub const PANEL_HEIGHT: f32 = 132.0;
+/// Entries listed in the expanded panel.
+const RECENT_ENTRIES: usize = 5;
+
+/// Conversion chips next to the result (fewer than on the cards, as they
+/// share the row with the result).
+const QUICK_CONVERSIONS: usize = 3;
 
 impl NumbatApp {
     /// Called from the root viewport each frame while the panel is open.
@@ -21,6 +34,8 @@ impl NumbatApp {
         // Compute the position once per open — it depends on the mouse
         // location, and the panel should not follow later mouse movement.
         if self.quick_just_opened {
+            self.quick_monitor = quick_panel_monitor(ctx);
+            self.quick_dragged_to = None;
             self.quick_position = self.quick_panel_position(ctx);
         }
 
@@ -29,7 +44,7 @@ impl NumbatApp {
         // default position before jumping to the center.
         let mut builder = ViewportBuilder::default()
             .with_title("Numbat Quick")
-            .with_inner_size([PANEL_WIDTH, PANEL_HEIGHT])
+            .with_inner_size([self.config.ui.quick_panel_width, self.quick_panel_height()])
             .with_decorations(false)
             .with_transparent(true)
             .with_resizable(false)
@@ -50,24 +65,61 @@ impl NumbatApp {
         );
     }
 
-    /// Roughly Spotlight's position: horizontally centered, upper third —
-    /// on the screen the mouse is on, falling back to the main window's.
+    /// Heights of the input row and the result row, which scale with the
+    /// panel's font size.
+    fn quick_panel_rows(&self) -> (f32, f32) {
+        let font_size = self.config.ui.quick_panel_font_size;
+        (font_size + 38.0, font_size + 52.0)
+    }
+
+    fn quick_panel_height(&self) -> f32 {
+        let (top, bottom) = self.quick_panel_rows();
+        let height = top + bottom + 2.0;
+        if self.quick_expanded {
+            height + EXPANDED_HEIGHT
+        } else {
+            height
+        }
+    }
+
+    /// Where the panel opens on the monitor the mouse is on: roughly
+    /// Spotlight's position (horizontally centered, upper third), under
+    /// the mouse, or where it was last dragged to on that monitor.
     fn quick_panel_position(&self, ctx: &egui::Context) -> Option<egui::Pos2> {
-        #[cfg(target_os = "macos")]
-        if let Some(screen) = crate::platform::screen_rect_under_mouse() {
-            return Some(egui::pos2(
-                screen.left() + (screen.width() - PANEL_WIDTH) / 2.0,
-                screen.top() + screen.height() * 0.22,
-            ));
+        let monitor = self.quick_monitor?;
+        let size = egui::vec2(self.config.ui.quick_panel_width, self.quick_panel_height());
+        let centered = egui::pos2(
+            monitor.center().x - size.x / 2.0,
+            monitor.top() + monitor.height() * 0.22,
+        );
+        let position = match self.config.ui.quick_panel_position {
+            QuickPanelPosition::Centered => centered,
+            QuickPanelPosition::UnderMouse => mouse_position(ctx)
+                .map(|mouse| mouse - egui::vec2(size.x / 2.0, 24.0))
+                .unwrap_or(centered),
+            QuickPanelPosition::Remembered => load_positions()
+                .get(&monitor_key(monitor))
+                .map(|[x, y]| monitor.min + egui::vec2(*x, *y))
+                .unwrap_or(centered),
+        };
+        Some(clamp_to_monitor(position, size, monitor))
+    }
+
+    /// Stores where the panel was dragged to, for the "last position" mode.
+    pub(crate) fn remember_quick_panel_position(&mut self) {
+        let (Some(position), Some(monitor)) = (self.quick_dragged_to.take(), self.quick_monitor)
+        else {
+            return;
+        };
+        if self.config.ui.quick_panel_position != QuickPanelPosition::Remembered {
+            return;
         }
-        let monitor = ctx.input(|i| i.viewport().monitor_size)?;
-        if monitor.x <= 0.0 || monitor.y <= 0.0 {
-            return None;
+        let mut positions = load_positions();
+        let offset = position - monitor.min;
+        positions.insert(monitor_key(monitor), [offset.x.round(), offset.y.round()]);
+        if let Err(e) = save_positions(&positions) {
+            log::warn!("{e}");
         }
-        Some(egui::pos2(
-            (monitor.x - PANEL_WIDTH) / 2.0,
-            monitor.y * 0.22,
-        ))
     }
 
     fn quick_panel_ui(&mut self, ui: &mut egui::Ui) {
@@ -106,8 +158,9 @@ impl NumbatApp {
         if focused == Some(true) {
             self.quick_had_focus = true;
         } else if self.quick_had_focus {
-            // Auto-hide once focus was gained and is lost (like Spotlight).
-            if focused == Some(false) {
+            // Auto-hide once focus was gained and is lost (like Spotlight),
+            // unless pinned.
+            if focused == Some(false) && !self.config.ui.quick_panel_pinned {
                 self.close_quick_panel();
                 return;
             }
@@ -123,35 +176,38 @@ impl NumbatApp {
             ctx.request_repaint_after(std::time::Duration::from_millis(100));
         }
 
-        // Escape: close the completion popup first, then the panel.
+        // Escape: close the completion popup first, then leave the recent
+        // entries, then close the panel.
         if !self.quick_completion.is_open()
             && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape))
+            && self.quick_selected.take().is_none()
         {
             self.close_quick_panel();
             return;
         }
 
-        // ⌘/Ctrl+⏎: continue in the full window.
-        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Enter)) {
-            self.open_main_window(ctx);
-            self.close_quick_panel();
-            return;
-        }
+        self.recent_entry_keys(ctx);
 
-        // ⌘/Ctrl+C without a text selection: copy the current result. The
-        // shortcut arrives as an `Event::Copy`, never as a key event; with
-        // a selection it is left for the input field to handle.
+        // Panel shortcuts: ⌘/Ctrl+⏎ continues in the full window, ⌘/Ctrl+C
+        // copies the result. A ⌘/Ctrl+C press arrives as an `Event::Copy`;
+        // with a text selection it is left for the input field to handle.
         let has_selection = egui::text_edit::TextEditState::load(ctx, egui::Id::new("quick_input"))
             .and_then(|state| state.cursor.char_range())
             .is_some_and(|range| !range.is_empty());
-        if !has_selection {
-            let copy_requested = ctx.input_mut(|i| {
-                let before = i.events.len();
-                i.events.retain(|e| !matches!(e, egui::Event::Copy));
-                i.events.len() != before
-            });
-            if copy_requested {
-                self.quick_copy_result(ctx);
+        let shortcuts = actions::shortcuts(&self.config.keybindings, Scope::QuickPanel);
+        let triggered = ctx.input_mut(|i| {
+            shortcuts
+                .into_iter()
+                .filter(|(shortcut, _)| {
+                    !has_selection || actions::clipboard_event(shortcut).is_none()
+                })
+                .find(|(shortcut, _)| actions::consume(i, shortcut))
+        });
+        if let Some((_, action)) = triggered {
+            let closes = action == Action::ContinueInMainWindow;
+            self.run_action(ctx, action);
+            if closes {
+                return;
             }
         }
 
@@ -172,14 +228,30 @@ impl NumbatApp {
                     .show(ui, |ui| {
                         // Fixed content size, also when rendered embedded
                         // (debug screenshot harness).
-                        let size = egui::vec2(ui.available_width(), PANEL_HEIGHT - 2.0);
+                        let size =
+                            egui::vec2(ui.available_width(), self.quick_panel_height() - 2.0);
                         ui.set_min_size(size);
                         ui.set_max_size(size);
+                        // The borderless panel moves by dragging its
+                        // background; widgets added later take precedence.
+                        let drag =
+                            ui.interact(ui.max_rect(), ui.id().with("drag"), egui::Sense::drag());
+                        let outer = ctx.input(|i| i.viewport().outer_rect);
+                        if drag.drag_started() {
+                            ctx.send_viewport_cmd(ViewportCommand::StartDrag);
+                            self.quick_dragged_to = outer.map(|r| r.min);
+                        } else if self.quick_dragged_to.is_some() {
+                            // The OS moves the window; follow it until the
+                            // panel closes.
+                            self.quick_dragged_to = outer.map(|r| r.min).or(self.quick_dragged_to);
+                        }
                         self.quick_panel_content(ui);
                     });
             });
 
-        self.toasts.ui(ctx, &palette, "quick");
+        if let Some(action) = self.toasts.ui(ctx, &palette, "quick") {
+            self.run_action(ctx, action);
+        }
     }
 
     fn quick_panel_content(&mut self, ui: &mut egui::Ui) {
@@ -187,7 +259,8 @@ impl NumbatApp {
         ui.spacing_mut().item_spacing = egui::vec2(0.0, 0.0);
 
         // Top row: logo, big input, "open in window" action.
-        let top_height = 58.0;
+        let (top_height, bottom_height) = self.quick_panel_rows();
+        let result_size = self.config.ui.quick_panel_font_size - 1.0;
         ui.allocate_ui_with_layout(
             egui::vec2(ui.available_width(), top_height),
             egui::Layout::left_to_right(egui::Align::Center),
@@ -207,14 +280,31 @@ impl NumbatApp {
                             .fill(palette.accent)
                             .corner_radius(CornerRadius::same(8))
                             .min_size(egui::vec2(34.0, 30.0));
-                    if ui
-                        .add(open)
-                        .on_hover_text("Open in window   (cmd/ctrl+enter)")
-                        .clicked()
-                    {
-                        let ctx = ui.ctx().clone();
-                        self.open_main_window(&ctx);
-                        self.close_quick_panel();
+                    let ctx = ui.ctx().clone();
+                    let action = Action::ContinueInMainWindow;
+                    let hint =
+                        match actions::shortcut_label(&ctx, &self.config.keybindings, &action) {
+                            Some(shortcut) => format!("Open in window   ({shortcut})"),
+                            None => "Open in window".to_owned(),
+                        };
+                    if ui.add(open).on_hover_text(hint).clicked() {
+                        self.run_action(&ctx, action);
+                    }
+
+                    let pinned = self.config.ui.quick_panel_pinned;
+                    let pin = egui::Button::new(RichText::new("📌").size(14.0).color(if pinned {
+                        palette.accent
+                    } else {
+                        palette.text_faint
+                    }))
+                    .frame(false);
+                    let pin_hint = if pinned {
+                        "Pinned: stays open when it loses focus"
+                    } else {
+                        "Pin: stay open when losing focus"
+                    };
+                    if ui.add(pin).on_hover_text(pin_hint).clicked() {
+                        self.set_quick_panel_pinned(!pinned);
                     }
 
                     ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
@@ -222,14 +312,19 @@ impl NumbatApp {
                             session: &mut self.session,
                             completion: &mut self.quick_completion,
                             palette: &palette,
-                            font_size: 20.0,
+                            font_size: self.config.ui.quick_panel_font_size,
                             hint: "Calculate…",
                             id: egui::Id::new("quick_input"),
+                            options: &self.config.input,
+                            kill_ring: &mut self.kill_ring,
                         }
                         .show(ui);
 
                         if result.submitted {
-                            self.session.submit();
+                            self.quick_submit(ui.ctx());
+                        }
+                        if result.response.changed() {
+                            self.quick_selected = None;
                         }
                         result.response.request_focus();
                     });
@@ -254,28 +349,35 @@ impl NumbatApp {
         let mut shown: Option<(egui::text::LayoutJob, Option<String>)> = None;
         let mut stale = false;
         let mut error: Option<String> = None;
+        // The line the conversion chips apply to, and their units.
+        let mut conversions: Option<(String, Vec<String>)> = None;
         if let Some(preview) = preview {
             shown = Some((
-                markup_job(&preview.markup, &palette, 19.0),
+                markup_job(&preview.markup, &palette, result_size),
                 Some(preview.plain),
             ));
             stale = !preview.fresh;
+            if preview.fresh {
+                conversions = Some((self.session.input.clone(), preview.conversions));
+            }
         } else if input_empty {
             if let Some(last) = self.session.entries.last() {
                 if let Some(result) = &last.result {
                     shown = Some((
-                        markup_job(result, &palette, 19.0),
+                        markup_job(result, &palette, result_size),
                         last.result_plain.clone(),
                     ));
+                    conversions = Some((last.input.clone(), last.conversions.clone()));
                 } else {
                     error = last.error.clone();
                 }
             }
         }
         let has_result = shown.is_some();
+        let mut convert: Option<String> = None;
 
         ui.allocate_ui_with_layout(
-            egui::vec2(ui.available_width(), ui.available_height().max(40.0)),
+            egui::vec2(ui.available_width(), bottom_height),
             egui::Layout::left_to_right(egui::Align::Center),
             |ui| {
                 ui.add_space(16.0);
@@ -301,6 +403,12 @@ impl NumbatApp {
                                 self.copy_to_clipboard(ui.ctx(), plain);
                             }
                         }
+                        if let Some((line, units)) = &conversions {
+                            let units = &units[..units.len().min(QUICK_CONVERSIONS)];
+                            if let Some(unit) = conversion_chips(ui, units, &palette) {
+                                convert = Some(with_conversion(line, &unit));
+                            }
+                        }
                     }
                     (None, Some(error)) => {
                         let first_line = error.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
@@ -321,25 +429,217 @@ impl NumbatApp {
                 }
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    ui.add_space(16.0);
-                    let cmd = if cfg!(target_os = "macos") {
-                        "cmd"
+                    ui.add_space(12.0);
+                    let (icon, tooltip) = if self.quick_expanded {
+                        ("⏶", "Hide recent entries")
                     } else {
-                        "ctrl"
+                        ("⏷", "Show recent entries")
                     };
-                    let hint = if has_result {
-                        format!("{cmd}+C copy  ·  esc")
-                    } else {
-                        "esc to close".to_owned()
+                    let toggle =
+                        egui::Button::new(RichText::new(icon).size(12.0).color(palette.text_faint))
+                            .frame(false);
+                    if ui.add(toggle).on_hover_text(tooltip).clicked() {
+                        self.quick_expanded = !self.quick_expanded;
+                        self.quick_selected = None;
+                    }
+                    ui.add_space(4.0);
+                    let copy = actions::shortcut_label(
+                        ui.ctx(),
+                        &self.config.keybindings,
+                        &Action::CopyCurrentResult,
+                    );
+                    let hint = match copy {
+                        Some(copy) if has_result => format!("{copy} copy  ·  esc"),
+                        _ => "esc to close".to_owned(),
                     };
                     ui.label(RichText::new(hint).size(11.0).color(palette.text_faint));
                 });
             },
         );
+
+        if let Some(line) = convert {
+            self.session.input = line;
+            self.session.on_input_edited();
+            self.quick_submit(ui.ctx());
+        }
+
+        if self.quick_expanded {
+            let y = ui.cursor().top();
+            ui.painter().hline(
+                rect.left()..=rect.right(),
+                y,
+                egui::Stroke::new(1.0, palette.border),
+            );
+            self.recent_entries_ui(ui);
+        }
+    }
+
+    fn set_quick_panel_pinned(&mut self, pinned: bool) {
+        self.config.ui.quick_panel_pinned = pinned;
+        self.settings_draft.ui.quick_panel_pinned = pinned;
+        if let Err(e) = self.config.save() {
+            log::warn!("{e}");
+        }
+    }
+
+    /// Enter in the panel: evaluates, then either keeps the panel open
+    /// (expanded, to show what a command like `list` printed), or copies
+    /// the result and closes — unless there is no result to copy.
+    fn quick_submit(&mut self, ctx: &egui::Context) {
+        let before = self.session.entries.len();
+        self.session.submit();
+        let added = self.session.entries.len() > before;
+        let result = added
+            .then(|| self.session.entries.last())
+            .flatten()
+            .and_then(|entry| entry.result_plain.clone());
+        match (self.config.ui.quick_panel_enter, result) {
+            (QuickPanelEnter::CopyAndClose, Some(result)) => {
+                self.copy_to_clipboard(ctx, result);
+                self.close_quick_panel();
+            }
+            _ => self.quick_expanded |= added,
+        }
+    }
+
+    /// Up/Down through the recent entries of the expanded panel, Enter to
+    /// copy one. Down from the prompt enters the list (unless it walks
+    /// through the command history), Up from the newest entry leaves it.
+    fn recent_entry_keys(&mut self, ctx: &egui::Context) {
+        if !self.quick_expanded || self.quick_completion.is_open() {
+            self.quick_selected = None;
+            return;
+        }
+        let count = self.session.entries.len().min(RECENT_ENTRIES);
+        let navigating = self.session.is_navigating_history();
+        let mut copy = None;
+        ctx.input_mut(|i| match self.quick_selected {
+            None => {
+                if count > 0 && !navigating && i.consume_key(Modifiers::NONE, Key::ArrowDown) {
+                    self.quick_selected = Some(0);
+                }
+            }
+            Some(selected) => {
+                if i.consume_key(Modifiers::NONE, Key::ArrowDown) && selected + 1 < count {
+                    self.quick_selected = Some(selected + 1);
+                }
+                if i.consume_key(Modifiers::NONE, Key::ArrowUp) {
+                    self.quick_selected = selected.checked_sub(1);
+                }
+                if i.consume_key(Modifiers::NONE, Key::Enter) {
+                    copy = Some(selected);
+                }
+            }
+        });
+        if let Some(selected) = copy {
+            self.copy_recent_entry(ctx, selected);
+        }
+    }
+
+    /// Copies the recent entry `index` (0 = newest).
+    fn copy_recent_entry(&mut self, ctx: &egui::Context, index: usize) {
+        let entry = self.session.entries.iter().rev().nth(index);
+        if let Some(text) = entry.and_then(|e| e.copy_text()) {
+            self.copy_to_clipboard(ctx, text);
+        }
+    }
+
+    /// The recent entries, newest first, as compact cards.
+    fn recent_entries_ui(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                ui.spacing_mut().item_spacing.y = 6.0;
+                ui.add_space(8.0);
+                if self.session.entries.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.add_space(16.0);
+                        ui.label(
+                            RichText::new("Nothing evaluated yet")
+                                .size(12.5)
+                                .color(palette.text_faint),
+                        );
+                    });
+                }
+                let recent = self.session.entries.iter().rev().take(RECENT_ENTRIES);
+                for (i, entry) in recent.enumerate() {
+                    let selected = self.quick_selected == Some(i);
+                    let response = Frame::new()
+                        .fill(if selected {
+                            palette.bg_raised
+                        } else {
+                            palette.bg
+                        })
+                        .stroke(Stroke::new(
+                            1.0,
+                            if selected {
+                                palette.accent
+                            } else {
+                                palette.border
+                            },
+                        ))
+                        .corner_radius(CornerRadius::same(8))
+                        .inner_margin(Margin::symmetric(10, 6))
+                        .outer_margin(Margin::symmetric(12, 0))
+                        .show(ui, |ui| {
+                            ui.set_min_width(ui.available_width());
+                            ui.spacing_mut().item_spacing.y = 3.0;
+                            ui.add(
+                                egui::Label::new(
+                                    RichText::new(&entry.input)
+                                        .monospace()
+                                        .size(12.0)
+                                        .color(palette.text_dim),
+                                )
+                                .truncate(),
+                            );
+                            for printed in &entry.printed {
+                                ui.add(
+                                    egui::Label::new(markup_job(printed, &palette, 12.5)).wrap(),
+                                );
+                            }
+                            if let Some(result) = &entry.result {
+                                ui.add(
+                                    egui::Label::new(markup_job(result, &palette, 14.0)).truncate(),
+                                );
+                            }
+                            if let Some(sweep) = &entry.sweep {
+                                sweep_table(ui, sweep, &palette, 12.0, 140.0);
+                            }
+                            if let Some(error) = &entry.error {
+                                let first_line =
+                                    error.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+                                ui.label(
+                                    RichText::new(first_line)
+                                        .monospace()
+                                        .size(12.0)
+                                        .color(palette.error),
+                                );
+                            }
+                        })
+                        .response
+                        .interact(egui::Sense::click())
+                        .on_hover_cursor(egui::CursorIcon::Copy);
+                    if selected {
+                        response.scroll_to_me(None);
+                    }
+                    if response.clicked() {
+                        clicked = Some(i);
+                    }
+                }
+                ui.add_space(8.0);
+            });
+        if let Some(index) = clicked {
+            self.quick_selected = Some(index);
+            let ctx = ui.ctx().clone();
+            self.copy_recent_entry(&ctx, index);
+        }
     }
 
     /// Copies the live preview if present, otherwise the last result.
-    fn quick_copy_result(&mut self, ctx: &egui::Context) {
+    pub fn quick_copy_result(&mut self, ctx: &egui::Context) {
         let preview = self.session.preview().map(|p| p.plain);
         let text = preview.or_else(|| self.session.last_result_plain().map(str::to_owned));
         if let Some(text) = text {
@@ -347,3 +647,107 @@ impl NumbatApp {
         }
     }
 }
+
+/// The monitor the panel opens on: the one under the mouse on macOS, X11
+/// and Windows, otherwise the main window's.
+fn quick_panel_monitor(ctx: &egui::Context) -> Option<egui::Rect> {
+    #[cfg(target_os = "macos")]
+    if let Some(screen) = crate::platform::screen_rect_under_mouse() {
+        return Some(screen);
+    }
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    if let Some(screen) = crate::platform::screen_rect_under_mouse() {
+        return Some(screen / native_pixels_per_point(ctx));
+    }
+    let size = ctx.input(|i| i.viewport().monitor_size)?;
+    (size.x > 0.0 && size.y > 0.0).then(|| egui::Rect::from_min_size(egui::Pos2::ZERO, size))
+}
+
+/// The mouse position in points.
+#[allow(unused_variables)]
+fn mouse_position(ctx: &egui::Context) -> Option<egui::Pos2> {
+    #[cfg(target_os = "macos")]
+    return crate::platform::mouse_position();
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    return crate::platform::mouse_position().map(|p| p / native_pixels_per_point(ctx));
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    None
+}
+
+/// X11 and Windows report pixels, winit places windows in points.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn native_pixels_per_point(ctx: &egui::Context) -> f32 {
+    ctx.input(|i| i.viewport().native_pixels_per_point)
+        .filter(|ppp| *ppp > 0.0)
+        .unwrap_or(1.0)
+}
+
+/// Moves a panel of `size` at `position` fully onto `monitor` (as far as
+/// it fits).
+fn clamp_to_monitor(position: egui::Pos2, size: egui::Vec2, monitor: egui::Rect) -> egui::Pos2 {
+    egui::pos2(
+        position.x.min(monitor.right() - size.x).max(monitor.left()),
+        position.y.min(monitor.bottom() - size.y).max(monitor.top()),
+    )
+}
+
+/// Identifies a monitor by its frame, e.g. `1920x1080@0,0`.
+fn monitor_key(monitor: egui::Rect) -> String {
+    format!(
+        "{}x{}@{},{}",
+        monitor.width().round(),
+        monitor.height().round(),
+        monitor.left().round(),
+        monitor.top().round()
+    )
+}
+
+fn positions_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("numbat-ui").join("quick-panel-positions.toml"))
+}
+
+/// The remembered panel positions, relative to their monitor's corner.
+fn load_positions() -> BTreeMap<String, [f32; 2]> {
+    positions_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| toml::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_positions(positions: &BTreeMap<String, [f32; 2]>) -> Result<(), String> {
+    let path = positions_path().ok_or("Could not locate the data directory")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create data directory: {e}"))?;
+    }
+    let content = toml::to_string(positions)
+        .map_err(|e| format!("Failed to serialize panel positions: {e}"))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_onto_the_monitor() {
+        let monitor = egui::Rect::from_min_size(egui::pos2(1920.0, 0.0), egui::vec2(1280.0, 800.0));
+        let size = egui::vec2(680.0, 132.0);
+        assert_eq!(
+            clamp_to_monitor(egui::pos2(3000.0, -50.0), size, monitor),
+            egui::pos2(2520.0, 0.0)
+        );
+        assert_eq!(
+            clamp_to_monitor(egui::pos2(2000.0, 100.0), size, monitor),
+            egui::pos2(2000.0, 100.0)
+        );
+    }
+
+    #[test]
+    fn monitor_keys_include_the_origin() {
+        let a = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(1920.0, 1080.0));
+        let b = egui::Rect::from_min_size(egui::pos2(1920.0, 0.0), egui::vec2(1920.0, 1080.0));
+        assert_eq!(monitor_key(a), "1920x1080@0,0");
+        assert_ne!(monitor_key(a), monitor_key(b));
+    }
+}
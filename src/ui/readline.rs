@@ -0,0 +1,347 @@
+//! The optional readline (Emacs) keymap of the prompt, as in the numbat CLI:
+//! line and word motions, kill commands feeding a kill ring, and yank.
+//!
+//! Commands work on character indices into the input; word boundaries are
+//! those of tab completion.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use egui::{Key, Modifiers};
+
+use super::{char_to_byte, is_word_char};
+
+/// Kills kept for Alt+Y.
+const KILL_RING_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    LineStart,
+    LineEnd,
+    CharBackward,
+    CharForward,
+    WordBackward,
+    WordForward,
+    HistoryPrevious,
+    HistoryNext,
+    DeleteChar,
+    KillLineEnd,
+    KillLineStart,
+    /// Ctrl+W: kills back to the previous whitespace.
+    KillBigWordBackward,
+    KillWordBackward,
+    KillWordForward,
+    Yank,
+    YankPop,
+}
+
+impl Command {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::LineStart => "Start of line",
+            Self::LineEnd => "End of line",
+            Self::CharBackward => "Back one character",
+            Self::CharForward => "Forward one character",
+            Self::WordBackward => "Back one word",
+            Self::WordForward => "Forward one word",
+            Self::HistoryPrevious => "Previous history entry",
+            Self::HistoryNext => "Next history entry",
+            Self::DeleteChar => "Delete character",
+            Self::KillLineEnd => "Kill to end of line",
+            Self::KillLineStart => "Kill to start of line",
+            Self::KillBigWordBackward => "Kill to previous whitespace",
+            Self::KillWordBackward => "Kill previous word",
+            Self::KillWordForward => "Kill next word",
+            Self::Yank => "Yank",
+            Self::YankPop => "Cycle through the kill ring",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Chord {
+    Ctrl,
+    Alt,
+}
+
+const BINDINGS: &[(Chord, Key, Command)] = &[
+    (Chord::Ctrl, Key::A, Command::LineStart),
+    (Chord::Ctrl, Key::E, Command::LineEnd),
+    (Chord::Ctrl, Key::B, Command::CharBackward),
+    (Chord::Ctrl, Key::F, Command::CharForward),
+    (Chord::Alt, Key::B, Command::WordBackward),
+    (Chord::Alt, Key::F, Command::WordForward),
+    (Chord::Ctrl, Key::P, Command::HistoryPrevious),
+    (Chord::Ctrl, Key::N, Command::HistoryNext),
+    (Chord::Ctrl, Key::D, Command::DeleteChar),
+    (Chord::Ctrl, Key::K, Command::KillLineEnd),
+    (Chord::Ctrl, Key::U, Command::KillLineStart),
+    (Chord::Ctrl, Key::W, Command::KillBigWordBackward),
+    (Chord::Alt, Key::Backspace, Command::KillWordBackward),
+    (Chord::Alt, Key::D, Command::KillWordForward),
+    (Chord::Ctrl, Key::Y, Command::Yank),
+    (Chord::Alt, Key::Y, Command::YankPop),
+];
+
+/// All bindings, for the shortcut overview.
+pub fn shortcuts() -> impl Iterator<Item = (egui::KeyboardShortcut, Command)> {
+    BINDINGS.iter().map(|&(chord, key, command)| {
+        let modifiers = match chord {
+            Chord::Ctrl => Modifiers::CTRL,
+            Chord::Alt => Modifiers::ALT,
+        };
+        (egui::KeyboardShortcut::new(modifiers, key), command)
+    })
+}
+
+/// The command bound to a key press. Modifiers must match exactly, and
+/// Ctrl is the physical Control key on macOS too (Cmd shortcuts stay
+/// untouched).
+pub fn command_for(modifiers: Modifiers, key: Key) -> Option<Command> {
+    if modifiers.shift || modifiers.mac_cmd {
+        return None;
+    }
+    let chord = match (modifiers.ctrl, modifiers.alt) {
+        (true, false) => Chord::Ctrl,
+        (false, true) => Chord::Alt,
+        _ => return None,
+    };
+    BINDINGS
+        .iter()
+        .find(|(c, k, _)| *c == chord && *k == key)
+        .map(|(_, _, command)| *command)
+}
+
+/// What the last command left behind, so that the next one can tell
+/// whether it continues it: consecutive kills accumulate into one entry,
+/// and Alt+Y only works right after a yank.
+struct LastEdit {
+    text: String,
+    cursor: usize,
+    yanked: Option<(Range<usize>, usize)>,
+}
+
+#[derive(Default)]
+pub struct KillRing {
+    /// Most recent kill first.
+    entries: VecDeque<String>,
+    last: Option<LastEdit>,
+}
+
+impl KillRing {
+    /// Runs an editing or motion command on `text` with the cursor at
+    /// `cursor`. Returns the new cursor. History commands are left to the
+    /// caller and return the cursor unchanged.
+    pub fn apply(&mut self, command: Command, text: &mut String, cursor: usize) -> usize {
+        let chars: Vec<char> = text.chars().collect();
+        let len = chars.len();
+        let cursor = cursor.min(len);
+        let last = self
+            .last
+            .take()
+            .filter(|last| last.text == *text && last.cursor == cursor);
+
+        match command {
+            Command::LineStart => 0,
+            Command::LineEnd => len,
+            Command::CharBackward => cursor.saturating_sub(1),
+            Command::CharForward => (cursor + 1).min(len),
+            Command::WordBackward => word_start(&chars, cursor),
+            Command::WordForward => word_end(&chars, cursor),
+            Command::HistoryPrevious | Command::HistoryNext => cursor,
+            Command::DeleteChar => {
+                if cursor < len {
+                    replace(text, cursor..cursor + 1, "");
+                }
+                cursor
+            }
+            Command::KillLineEnd => self.kill(text, cursor..len, cursor, last),
+            Command::KillLineStart => self.kill(text, 0..cursor, cursor, last),
+            Command::KillBigWordBackward => {
+                let mut start = cursor;
+                while start > 0 && chars[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                while start > 0 && !chars[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                self.kill(text, start..cursor, cursor, last)
+            }
+            Command::KillWordBackward => {
+                self.kill(text, word_start(&chars, cursor)..cursor, cursor, last)
+            }
+            Command::KillWordForward => {
+                self.kill(text, cursor..word_end(&chars, cursor), cursor, last)
+            }
+            Command::Yank => match self.entries.front().cloned() {
+                Some(entry) => self.yank(text, cursor..cursor, &entry, 0),
+                None => cursor,
+            },
+            Command::YankPop => match last.and_then(|last| last.yanked) {
+                Some((range, index)) if self.entries.len() > 1 => {
+                    let index = (index + 1) % self.entries.len();
+                    let entry = self.entries[index].clone();
+                    self.yank(text, range, &entry, index)
+                }
+                _ => cursor,
+            },
+        }
+    }
+
+    fn kill(
+        &mut self,
+        text: &mut String,
+        range: Range<usize>,
+        cursor: usize,
+        last: Option<LastEdit>,
+    ) -> usize {
+        if range.is_empty() {
+            self.last = last;
+            return range.start;
+        }
+        let killed: String = text.chars().skip(range.start).take(range.len()).collect();
+        let continues = last.is_some_and(|last| last.yanked.is_none());
+        match self.entries.front_mut() {
+            // Killing backwards prepends to the previous kill, forwards appends.
+            Some(entry) if continues => {
+                if range.end == cursor {
+                    entry.insert_str(0, &killed);
+                } else {
+                    entry.push_str(&killed);
+                }
+            }
+            _ => {
+                self.entries.push_front(killed);
+                self.entries.truncate(KILL_RING_SIZE);
+            }
+        }
+        replace(text, range.clone(), "");
+        self.last = Some(LastEdit {
+            text: text.clone(),
+            cursor: range.start,
+            yanked: None,
+        });
+        range.start
+    }
+
+    fn yank(&mut self, text: &mut String, range: Range<usize>, entry: &str, index: usize) -> usize {
+        replace(text, range.clone(), entry);
+        let end = range.start + entry.chars().count();
+        self.last = Some(LastEdit {
+            text: text.clone(),
+            cursor: end,
+            yanked: Some((range.start..end, index)),
+        });
+        end
+    }
+}
+
+/// Start of the word before `cursor`, skipping separators first.
+fn word_start(chars: &[char], cursor: usize) -> usize {
+    let mut i = cursor;
+    while i > 0 && !is_word_char(chars[i - 1]) {
+        i -= 1;
+    }
+    while i > 0 && is_word_char(chars[i - 1]) {
+        i -= 1;
+    }
+    i
+}
+
+/// End of the word after `cursor`, skipping separators first.
+fn word_end(chars: &[char], cursor: usize) -> usize {
+    let mut i = cursor;
+    while i < chars.len() && !is_word_char(chars[i]) {
+        i += 1;
+    }
+    while i < chars.len() && is_word_char(chars[i]) {
+        i += 1;
+    }
+    i
+}
+
+fn replace(text: &mut String, range: Range<usize>, with: &str) {
+    let range = char_to_byte(text, range.start)..char_to_byte(text, range.end);
+    text.replace_range(range, with);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(ring: &mut KillRing, text: &mut String, cursor: usize, commands: &[Command]) -> usize {
+        commands
+            .iter()
+            .fold(cursor, |cursor, &command| ring.apply(command, text, cursor))
+    }
+
+    #[test]
+    fn motions() {
+        let mut ring = KillRing::default();
+        let mut text = "3 meter -> feet".to_owned();
+        assert_eq!(ring.apply(Command::LineStart, &mut text, 5), 0);
+        assert_eq!(ring.apply(Command::LineEnd, &mut text, 5), 15);
+        assert_eq!(ring.apply(Command::WordBackward, &mut text, 15), 11);
+        assert_eq!(ring.apply(Command::WordBackward, &mut text, 11), 2);
+        assert_eq!(ring.apply(Command::WordForward, &mut text, 2), 7);
+        assert_eq!(ring.apply(Command::WordForward, &mut text, 7), 15);
+    }
+
+    #[test]
+    fn kill_and_yank() {
+        let mut ring = KillRing::default();
+        let mut text = "2 km + 300 m".to_owned();
+        let cursor = run(&mut ring, &mut text, 4, &[Command::KillLineEnd]);
+        assert_eq!((text.as_str(), cursor), ("2 km", 4));
+        let cursor = run(&mut ring, &mut text, 0, &[Command::Yank]);
+        assert_eq!((text.as_str(), cursor), (" + 300 m2 km", 8));
+    }
+
+    #[test]
+    fn consecutive_kills_accumulate() {
+        let mut ring = KillRing::default();
+        let mut text = "a = 1 + 2".to_owned();
+        run(
+            &mut ring,
+            &mut text,
+            9,
+            &[Command::KillBigWordBackward, Command::KillBigWordBackward],
+        );
+        assert_eq!(text, "a = 1 ");
+        run(&mut ring, &mut text, 0, &[Command::Yank]);
+        assert_eq!(text, "+ 2a = 1 ");
+    }
+
+    #[test]
+    fn yank_pop_cycles() {
+        let mut ring = KillRing::default();
+        let mut text = "one two".to_owned();
+        let cursor = run(&mut ring, &mut text, 7, &[Command::KillWordBackward]);
+        // Moving away ends the kill, so the next one is a new entry.
+        let cursor = run(&mut ring, &mut text, cursor, &[Command::CharBackward]);
+        run(&mut ring, &mut text, cursor, &[Command::KillLineStart]);
+        assert_eq!(text, " ");
+        let cursor = run(&mut ring, &mut text, 0, &[Command::Yank]);
+        assert_eq!(text, "one ");
+        run(&mut ring, &mut text, cursor, &[Command::YankPop]);
+        assert_eq!(text, "two ");
+    }
+
+    #[test]
+    fn bindings_require_exact_modifiers() {
+        assert_eq!(
+            command_for(Modifiers::CTRL, Key::A),
+            Some(Command::LineStart)
+        );
+        assert_eq!(
+            command_for(Modifiers::ALT, Key::B),
+            Some(Command::WordBackward)
+        );
+        assert_eq!(
+            command_for(Modifiers::CTRL | Modifiers::SHIFT, Key::A),
+            None
+        );
+        assert_eq!(command_for(Modifiers::MAC_CMD, Key::A), None);
+        assert_eq!(command_for(Modifiers::NONE, Key::A), None);
+    }
+}
@@ -0,0 +1,120 @@
+//! Scrubbing of number literals in the prompt: scrolling over a number, or
+//! dragging it with Alt held, steps it by its last digit (`3.25` by
+//! `0.01`), so the live preview turns into a what-if tool.
+
+use std::ops::Range;
+
+use crate::syntax::{self, TokenKind};
+
+/// Scroll distance (in points) per step.
+pub const SCROLL_STEP: f32 = 20.0;
+
+/// Horizontal drag distance (in points) per step.
+pub const DRAG_STEP: f32 = 8.0;
+
+/// A drag in progress, kept in egui's temporary data.
+#[derive(Clone)]
+pub struct Drag {
+    /// Byte offset of the literal in the input.
+    pub start: usize,
+    /// The literal when the drag started; steps are counted from it.
+    pub original: String,
+    /// Byte length of the literal currently in the input.
+    pub len: usize,
+    pub origin_x: f32,
+}
+
+/// Byte range of the scrubbable literal at (or right next to) the byte
+/// offset `at`.
+pub fn literal_at(text: &str, at: usize) -> Option<Range<usize>> {
+    syntax::tokenize(text)
+        .into_iter()
+        .find(|t| t.kind == TokenKind::Number && t.range.start <= at && at <= t.range.end)
+        .map(|t| t.range)
+        .filter(|range| step(&text[range.clone()], 0).is_some())
+}
+
+/// The literal moved by `steps` units of its last digit, keeping its
+/// number of decimals and digit grouping. Stops at zero. Only plain
+/// decimal literals can be scrubbed, not `1e3` or `0x1F`.
+pub fn step(literal: &str, steps: i64) -> Option<String> {
+    let (int_part, frac_part) = match literal.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (literal, None),
+    };
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit() || c == '_');
+    if !digits(int_part) || !frac_part.is_none_or(|f| !f.is_empty() && digits(f)) {
+        return None;
+    }
+
+    let frac: String = frac_part
+        .unwrap_or("")
+        .chars()
+        .filter(|&c| c != '_')
+        .collect();
+    let int: String = int_part.chars().filter(|&c| c != '_').collect();
+    let scaled: i128 = format!("{int}{frac}").parse().ok()?;
+    let value = (scaled + i128::from(steps)).max(0);
+
+    let decimals = frac.len();
+    let mut text = format!("{value:0>width$}", width = decimals + 1);
+    let frac = text.split_off(text.len() - decimals);
+    let mut int = text;
+    if int_part.contains('_') {
+        int = group(&int);
+    } else if int_part.is_empty() && int == "0" {
+        int.clear(); // `.5` stays `.6`
+    }
+    Some(match frac_part {
+        Some(_) => format!("{int}.{frac}"),
+        None => int,
+    })
+}
+
+/// `1234567` → `1_234_567`.
+fn group(int: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i).is_multiple_of(3) {
+            out.push('_');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_by_last_digit() {
+        assert_eq!(step("3", 1).as_deref(), Some("4"));
+        assert_eq!(step("3.25", 1).as_deref(), Some("3.26"));
+        assert_eq!(step("3.25", -30).as_deref(), Some("2.95"));
+        assert_eq!(step("0.99", 1).as_deref(), Some("1.00"));
+        assert_eq!(step(".5", 1).as_deref(), Some(".6"));
+        assert_eq!(step("1_999", 1).as_deref(), Some("2_000"));
+    }
+
+    #[test]
+    fn stops_at_zero() {
+        assert_eq!(step("2", -5).as_deref(), Some("0"));
+        assert_eq!(step("0.3", -5).as_deref(), Some("0.0"));
+    }
+
+    #[test]
+    fn only_plain_decimals() {
+        assert_eq!(step("1e3", 1), None);
+        assert_eq!(step("0x1F", 1), None);
+    }
+
+    #[test]
+    fn finds_literal_under_pointer() {
+        let text = "2 km + 1.5 m";
+        assert_eq!(literal_at(text, 0), Some(0..1));
+        assert_eq!(literal_at(text, 9), Some(7..10));
+        assert_eq!(literal_at(text, 3), None);
+        assert_eq!(literal_at("1e3 m", 1), None);
+    }
+}
@@ -5,9 +5,13 @@ use egui::{
     ViewportBuilder, ViewportId,
 };
 
+use crate::actions::{self, Action, Scope};
 use crate::app::NumbatApp;
-use crate::config::{ThemeChoice, DEFAULT_QUICK_PANEL_HOTKEY};
-use crate::hotkey;
+use crate::config::{
+    Keymap, QuickPanelEnter, QuickPanelPosition, ThemeChoice, UnitSystem,
+    DEFAULT_QUICK_PANEL_HOTKEY,
+};
+use crate::hotkey::{self, GlobalAction};
 
 const SEPARATOR_CHOICES: [(&str, &str); 5] = [
     ("_", "Underscore  1_000_000"),
@@ -21,6 +25,25 @@ impl NumbatApp {
     pub fn open_settings(&mut self) {
         self.settings_draft = self.config.clone();
         self.settings_error = None;
+        self.recording_shortcut = None;
+        for (dimension, unit) in &self.settings_draft.units.targets {
+            let of_dimension = self.session.engine.dimension_of(unit).and_then(|actual| {
+                let matches = actual
+                    .split(" or ")
+                    .any(|name| name.eq_ignore_ascii_case(dimension));
+                if matches {
+                    Ok(())
+                } else {
+                    Err(format!("{unit} is a unit of {actual}, not {dimension}"))
+                }
+            });
+            if let Err(e) = of_dimension {
+                self.settings_error = Some(e);
+                return;
+            }
+        }
+        self.recording_hotkey = None;
+        self.hotkey_check = None;
         self.show_settings = true;
     }
 
@@ -140,40 +163,83 @@ impl NumbatApp {
                 });
             });
 
-            section(ui, "QUICK PANEL", &mut |ui| {
+            section(ui, "UNITS", &mut |ui| {
+                self.units_ui(ui);
+            });
+
+            section(ui, "EDITING", &mut |ui| {
+                ui.checkbox(
+                    &mut self.settings_draft.input.auto_close_brackets,
+                    "Auto-close brackets and quotes",
+                );
+                ui.label(
+                    RichText::new("Typing ( [ { or \" inserts the closing partner.")
+                        .size(11.0)
+                        .color(palette.text_faint),
+                );
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Key bindings");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        for keymap in [Keymap::Emacs, Keymap::Standard] {
+                            ui.selectable_value(
+                                &mut self.settings_draft.input.keymap,
+                                keymap,
+                                keymap.label(),
+                            );
+                        }
+                    });
+                });
+                ui.label(
+                    RichText::new("Emacs: Ctrl+A/E/B/F, Alt+B/F, Ctrl+K/U/W kill, Ctrl+Y yank, Alt+Y cycle.")
+                        .size(11.0)
+                        .color(palette.text_faint),
+                );
+                ui.add_space(6.0);
+                ui.checkbox(
+                    &mut self.settings_draft.input.symbol_substitutions,
+                    "Replace -> <= >= != with symbols",
+                );
+                ui.label(
+                    RichText::new("Typed as → ≤ ≥ ≠. Tab expands \\alpha, \\mu, ^2 and friends either way.")
+                        .size(11.0)
+                        .color(palette.text_faint),
+                );
+                ui.add_space(6.0);
+                ui.checkbox(
+                    &mut self.settings_draft.input.recompute_dependents,
+                    "Reactive history",
+                );
+                ui.label(
+                    RichText::new("After editing a card in place (✎), the later entries that depend on it are re-run too and highlighted when their result changes.")
+                        .size(11.0)
+                        .color(palette.text_faint),
+                );
+                ui.add_space(6.0);
                 ui.horizontal(|ui| {
-                    ui.label("Global hotkey");
+                    ui.label("Refresh clock watches every");
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                         ui.add(
-                            egui::TextEdit::singleline(
-                                &mut self.settings_draft.ui.quick_panel_hotkey,
-                            )
-                            .desired_width(180.0)
-                            .font(egui::TextStyle::Monospace),
+                            egui::DragValue::new(&mut self.settings_draft.watch.refresh_seconds)
+                                .range(0.0..=3600.0)
+                                .speed(0.1)
+                                .suffix(" s"),
                         );
                     });
                 });
-                match hotkey::parse_combo(&self.settings_draft.ui.quick_panel_hotkey) {
-                    Ok(_) => {
-                        ui.label(
-                            RichText::new(format!(
-                                "Shows as:  {}",
-                                hotkey::display_combo(
-                                    &self.settings_draft.ui.quick_panel_hotkey
-                                )
-                            ))
-                            .size(11.0)
-                            .color(palette.text_dim),
-                        );
-                    }
-                    Err(_) => {
-                        ui.label(
-                            RichText::new("Not a valid combo — e.g. Alt+Space, Cmd+Shift+KeyK")
-                                .size(11.0)
-                                .color(palette.error),
-                        );
-                    }
-                }
+                ui.label(
+                    RichText::new("Watches using now() or today() update on this interval (0: only when definitions change).")
+                        .size(11.0)
+                        .color(palette.text_faint),
+                );
+            });
+
+            section(ui, "KEYBOARD SHORTCUTS", &mut |ui| {
+                self.keybindings_ui(ui);
+            });
+
+            section(ui, "QUICK PANEL", &mut |ui| {
+                self.hotkey_row(ui, GlobalAction::ToggleQuickPanel, "Global hotkey");
                 ui.label(
                     RichText::new(format!(
                         "Summons the quick calculator from anywhere. Default: {DEFAULT_QUICK_PANEL_HOTKEY}"
@@ -181,9 +247,74 @@ impl NumbatApp {
                     .size(11.0)
                     .color(palette.text_faint),
                 );
-                if let Some(error) = &self.hotkey_error {
-                    ui.label(RichText::new(error).size(11.0).color(palette.error));
-                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Enter");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        for mode in [QuickPanelEnter::CopyAndClose, QuickPanelEnter::Evaluate] {
+                            ui.selectable_value(
+                                &mut self.settings_draft.ui.quick_panel_enter,
+                                mode,
+                                mode.label(),
+                            );
+                        }
+                    });
+                });
+                ui.checkbox(
+                    &mut self.settings_draft.ui.quick_panel_pinned,
+                    "Keep open when it loses focus",
+                );
+                ui.checkbox(
+                    &mut self.settings_draft.ui.quick_panel_clear_on_close,
+                    "Clear the input on close",
+                );
+                ui.label(
+                    RichText::new("Otherwise the draft is still there next time.")
+                        .size(11.0)
+                        .color(palette.text_faint),
+                );
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Width");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        ui.add(
+                            Slider::new(&mut self.settings_draft.ui.quick_panel_width, 480.0..=1000.0)
+                                .step_by(10.0)
+                                .suffix(" pt"),
+                        );
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Font size");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        ui.add(
+                            Slider::new(&mut self.settings_draft.ui.quick_panel_font_size, 14.0..=28.0)
+                                .step_by(1.0)
+                                .fixed_decimals(0),
+                        );
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Position");
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        for mode in [
+                            QuickPanelPosition::Remembered,
+                            QuickPanelPosition::UnderMouse,
+                            QuickPanelPosition::Centered,
+                        ] {
+                            ui.selectable_value(
+                                &mut self.settings_draft.ui.quick_panel_position,
+                                mode,
+                                mode.label(),
+                            );
+                        }
+                    });
+                });
+                ui.label(
+                    RichText::new("Drag the panel by its background to move it. The last position is remembered per monitor.")
+                        .size(11.0)
+                        .color(palette.text_faint),
+                );
 
                 ui.add_space(6.0);
                 ui.checkbox(
@@ -197,6 +328,37 @@ impl NumbatApp {
                 );
             });
 
+            section(ui, "GLOBAL HOTKEYS", &mut |ui| {
+                for action in [
+                    GlobalAction::ShowMainWindow,
+                    GlobalAction::CopyLastResult,
+                    GlobalAction::EvaluateClipboard,
+                ] {
+                    self.hotkey_row(ui, action, action.label());
+                }
+                if cfg!(all(unix, not(target_os = "macos"))) {
+                    ui.checkbox(
+                        &mut self.settings_draft.ui.evaluate_primary_selection,
+                        "Evaluate the selected text instead (X11 primary selection)",
+                    );
+                }
+                ui.label(
+                    RichText::new("Work from any app. Evaluating the clipboard shows the result in the quick panel.")
+                        .size(11.0)
+                        .color(palette.text_faint),
+                );
+                for combo in hotkey::duplicate_combos(&self.settings_draft.ui) {
+                    ui.label(
+                        RichText::new(format!("{} is used twice.", hotkey::display_combo(&combo)))
+                            .size(11.0)
+                            .color(palette.error),
+                    );
+                }
+                if let Some(error) = &self.hotkey_error {
+                    ui.label(RichText::new(error).size(11.0).color(palette.error));
+                }
+            });
+
             if let Some(error) = &self.settings_error {
                 ui.label(RichText::new(error).color(palette.error).size(12.0));
                 ui.add_space(6.0);
@@ -227,12 +389,355 @@ impl NumbatApp {
         });
     }
 
+    /// The preferred unit system, and the per-dimension targets of the
+    /// custom one.
+    fn units_ui(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        let units = &mut self.settings_draft.units;
+        ui.horizontal(|ui| {
+            ui.label("Show results in");
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                for system in [
+                    UnitSystem::Custom,
+                    UnitSystem::Imperial,
+                    UnitSystem::Si,
+                    UnitSystem::AsComputed,
+                ] {
+                    ui.selectable_value(&mut units.system, system, system.label());
+                }
+            });
+        });
+        ui.label(
+            RichText::new("Unless the input converts with ->. Results note the unit they were computed in; click it to switch back.")
+                .size(11.0)
+                .color(palette.text_faint),
+        );
+        if units.system != UnitSystem::Custom {
+            return;
+        }
+
+        ui.add_space(6.0);
+        let mut remove = None;
+        for (dimension, unit) in &units.targets {
+            ui.horizontal(|ui| {
+                ui.label(format!("{dimension} → {unit}"));
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    let button =
+                        egui::Button::new(RichText::new("×").size(13.0).color(palette.text_dim))
+                            .frame(false);
+                    if ui.add(button).on_hover_text("Remove").clicked() {
+                        remove = Some(dimension.clone());
+                    }
+                });
+            });
+        }
+        if let Some(dimension) = remove {
+            units.targets.remove(&dimension);
+        }
+
+        let (dimension, unit) = &mut self.new_unit_target;
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(dimension)
+                    .hint_text("Dimension, e.g. energy")
+                    .desired_width(150.0),
+            );
+            ui.label("→");
+            ui.add(
+                egui::TextEdit::singleline(unit)
+                    .hint_text("Unit, e.g. kWh")
+                    .desired_width(90.0),
+            );
+            let complete = !dimension.trim().is_empty() && !unit.trim().is_empty();
+            if ui.add_enabled(complete, egui::Button::new("Add")).clicked() {
+                units
+                    .targets
+                    .insert(dimension.trim().to_lowercase(), unit.trim().to_owned());
+                dimension.clear();
+                unit.clear();
+            }
+        });
+    }
+
+    /// The shortcut editor: per action its shortcuts (click one to remove
+    /// it), + to record a new one and ↺ to restore the defaults.
+    fn keybindings_ui(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        self.record_shortcut(ui);
+
+        let conflicts = actions::conflicts(&self.settings_draft.keybindings);
+        let in_conflict = |shortcut: &egui::KeyboardShortcut| {
+            conflicts.iter().any(|(s, _)| {
+                s.logical_key == shortcut.logical_key
+                    && actions::pressed_modifiers(s.modifiers)
+                        == actions::pressed_modifiers(shortcut.modifiers)
+            })
+        };
+
+        for scope in [Scope::MainWindow, Scope::QuickPanel] {
+            ui.label(
+                RichText::new(scope.label())
+                    .size(11.0)
+                    .color(palette.text_faint),
+            );
+            for action in Action::bindable() {
+                if action.scope() != scope {
+                    continue;
+                }
+                let mut bindings =
+                    actions::binding_strings(&self.settings_draft.keybindings, &action);
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    ui.label(action.title());
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let overridden = action
+                            .id()
+                            .is_some_and(|id| self.settings_draft.keybindings.0.contains_key(id));
+                        if overridden
+                            && ui
+                                .small_button("↺")
+                                .on_hover_text("Restore the default")
+                                .clicked()
+                        {
+                            bindings = action
+                                .default_bindings()
+                                .iter()
+                                .map(|s| (*s).to_owned())
+                                .collect();
+                            changed = true;
+                        }
+
+                        let recording = self.recording_shortcut.as_ref() == Some(&action);
+                        let add = if recording { "Press keys…" } else { "+" };
+                        let add = ui
+                            .add(egui::Button::new(RichText::new(add).size(11.5)).small())
+                            .on_hover_text(if recording {
+                                "Esc cancels"
+                            } else {
+                                "Record a shortcut"
+                            });
+                        if add.clicked() {
+                            self.recording_shortcut = (!recording).then(|| action.clone());
+                        }
+
+                        // Right to left, so the last one first.
+                        for i in (0..bindings.len()).rev() {
+                            let (text, color) = match actions::parse_shortcut(&bindings[i]) {
+                                Ok(shortcut) if in_conflict(&shortcut) => {
+                                    (ui.ctx().format_shortcut(&shortcut), palette.error)
+                                }
+                                Ok(shortcut) => {
+                                    (ui.ctx().format_shortcut(&shortcut), palette.text_dim)
+                                }
+                                Err(_) => (bindings[i].clone(), palette.error),
+                            };
+                            let chip = egui::Button::new(
+                                RichText::new(text).monospace().size(11.5).color(color),
+                            )
+                            .small();
+                            if ui.add(chip).on_hover_text("Click to remove").clicked() {
+                                bindings.remove(i);
+                                changed = true;
+                            }
+                        }
+                    });
+                });
+                if changed {
+                    actions::set_binding_strings(
+                        &mut self.settings_draft.keybindings,
+                        &action,
+                        bindings,
+                    );
+                }
+            }
+            ui.add_space(6.0);
+        }
+
+        for (shortcut, actions) in &conflicts {
+            let titles: Vec<String> = actions.iter().map(Action::title).collect();
+            ui.label(
+                RichText::new(format!(
+                    "{} is bound to {}.",
+                    ui.ctx().format_shortcut(shortcut),
+                    titles.join(" and ")
+                ))
+                .size(11.0)
+                .color(palette.error),
+            );
+        }
+        if self.settings_draft.input.keymap == Keymap::Emacs {
+            for (shortcut, action) in
+                actions::shortcuts(&self.settings_draft.keybindings, Scope::MainWindow)
+            {
+                if actions::shadowed_by_readline(&shortcut) {
+                    let note = if action == Action::Quit {
+                        "only on an empty prompt"
+                    } else {
+                        "the prompt takes it"
+                    };
+                    ui.label(
+                        RichText::new(format!(
+                            "{} is an Emacs key too — {note}.",
+                            ui.ctx().format_shortcut(&shortcut),
+                        ))
+                        .size(11.0)
+                        .color(palette.text_faint),
+                    );
+                }
+            }
+        }
+        ui.label(
+            RichText::new("F1 shows all shortcuts.")
+                .size(11.0)
+                .color(palette.text_faint),
+        );
+    }
+
+    /// While recording, turns the next key press in the settings window
+    /// into a shortcut for the recorded action.
+    fn record_shortcut(&mut self, ui: &mut egui::Ui) {
+        let Some(action) = self.recording_shortcut.clone() else {
+            return;
+        };
+        let Some((modifiers, key)) = take_key_press(ui) else {
+            return;
+        };
+        let shortcut = actions::shortcut_from_press(modifiers, key);
+        self.recording_shortcut = None;
+        if shortcut.modifiers.is_none() && shortcut.logical_key == egui::Key::Escape {
+            return;
+        }
+        let function_key = shortcut
+            .logical_key
+            .name()
+            .strip_prefix('F')
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+        let m = shortcut.modifiers;
+        if !(m.command || m.ctrl || m.alt || function_key) {
+            self.settings_error = Some(format!(
+                "{} alone would get in the way of typing — add Cmd, Ctrl or Alt.",
+                ui.ctx().format_shortcut(&shortcut)
+            ));
+            return;
+        }
+        self.settings_error = None;
+        let mut bindings = actions::binding_strings(&self.settings_draft.keybindings, &action);
+        let text = actions::format_shortcut(&shortcut);
+        if !bindings.contains(&text) {
+            bindings.push(text);
+        }
+        actions::set_binding_strings(&mut self.settings_draft.keybindings, &action, bindings);
+    }
+
+    /// A global hotkey with its recorder: a button showing the hotkey,
+    /// which records the next chord when clicked and test-registers it.
+    /// Hotkeys other than the quick panel's can be removed with ×.
+    fn hotkey_row(&mut self, ui: &mut egui::Ui, action: GlobalAction, label: &str) {
+        let palette = self.palette;
+        let recording = self.recording_hotkey == Some(action);
+        if recording {
+            if let Some((modifiers, key)) = take_key_press(ui) {
+                self.recording_hotkey = None;
+                if !(modifiers.is_none() && key == egui::Key::Escape) {
+                    let result = hotkey::combo_from_press(modifiers, key).and_then(|combo| {
+                        match &self.hotkeys {
+                            Some(hotkeys) => hotkeys.check(&combo)?,
+                            None => return Err("Global hotkeys are unavailable".to_owned()),
+                        }
+                        *action.combo_mut(&mut self.settings_draft.ui) = combo;
+                        Ok(())
+                    });
+                    self.hotkey_check = Some((action, result));
+                }
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(label);
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                let combo = action.combo(&self.settings_draft.ui);
+                if action != GlobalAction::ToggleQuickPanel
+                    && !combo.is_empty()
+                    && ui.small_button("×").on_hover_text("Remove").clicked()
+                {
+                    action.combo_mut(&mut self.settings_draft.ui).clear();
+                    self.hotkey_check = None;
+                }
+                let combo = action.combo(&self.settings_draft.ui);
+                let text = if self.recording_hotkey == Some(action) {
+                    "Press the shortcut…".to_owned()
+                } else if combo.is_empty() {
+                    "None".to_owned()
+                } else {
+                    hotkey::display_combo(combo)
+                };
+                let button = egui::Button::new(RichText::new(text).monospace())
+                    .min_size(egui::vec2(180.0, 0.0));
+                let response = ui.add(button).on_hover_text(if recording {
+                    "Esc cancels"
+                } else {
+                    "Click, then press the new hotkey"
+                });
+                if response.clicked() {
+                    self.recording_hotkey = (!recording).then_some(action);
+                }
+            });
+        });
+
+        match &self.hotkey_check {
+            Some((checked, Ok(()))) if *checked == action => {
+                ui.label(
+                    RichText::new("✔ Available — applies on save.")
+                        .size(11.0)
+                        .color(palette.text_dim),
+                );
+            }
+            Some((checked, Err(e))) if *checked == action => {
+                ui.label(RichText::new(e).size(11.0).color(palette.error));
+            }
+            _ => {}
+        }
+    }
+
     fn apply_settings(&mut self, ctx: &egui::Context) {
-        // Validate the hotkey before touching anything.
+        // Validate the hotkeys and the shortcuts before touching anything.
         if let Err(e) = hotkey::parse_combo(&self.settings_draft.ui.quick_panel_hotkey) {
             self.settings_error = Some(e);
             return;
         }
+        for action in GlobalAction::ALL {
+            let combo = action.combo(&self.settings_draft.ui);
+            if combo.trim().is_empty() {
+                continue;
+            }
+            if let Err(e) = hotkey::parse_combo(combo) {
+                self.settings_error = Some(e);
+                return;
+            }
+        }
+        if let Some(combo) = hotkey::duplicate_combos(&self.settings_draft.ui).first() {
+            self.settings_error = Some(format!(
+                "{} is used for two global hotkeys.",
+                hotkey::display_combo(combo)
+            ));
+            return;
+        }
+        for action in Action::bindable() {
+            for text in actions::binding_strings(&self.settings_draft.keybindings, &action) {
+                if let Err(e) = actions::parse_shortcut(&text) {
+                    self.settings_error = Some(format!("{e} for {}", action.title()));
+                    return;
+                }
+            }
+        }
+        if let Some((shortcut, _)) = actions::conflicts(&self.settings_draft.keybindings).first() {
+            self.settings_error = Some(format!(
+                "{} is bound twice — remove one of them first.",
+                ctx.format_shortcut(shortcut)
+            ));
+            return;
+        }
+        self.recording_shortcut = None;
 
         // Register/unregister launch-at-login before persisting the config.
         if self.settings_draft.ui.launch_at_login != self.config.ui.launch_at_login {
@@ -247,22 +752,20 @@ impl NumbatApp {
             return;
         }
 
-        let formatting_changed = self.config.formatting != self.settings_draft.formatting;
-        let hotkey_changed =
-            self.config.ui.quick_panel_hotkey != self.settings_draft.ui.quick_panel_hotkey;
-        self.config = self.settings_draft.clone();
+        let formatting_changed = self.config.formatting != self.settings_draft.formatting
+            || self.config.units != self.settings_draft.units;
+        let previous = std::mem::replace(&mut self.config, self.settings_draft.clone());
 
-        // Re-register the global hotkey.
-        if hotkey_changed {
-            if let Some(hotkey) = &mut self.hotkey {
-                self.hotkey_error = hotkey.register(&self.config.ui.quick_panel_hotkey).err();
-            }
+        // Re-register the global hotkeys that changed.
+        if let Some(hotkeys) = &mut self.hotkeys {
+            self.hotkey_error = hotkeys.register_all(&self.config.ui);
         }
+        self.recording_hotkey = None;
+        self.hotkey_check = None;
 
-        // Reformat existing results with the new options.
+        // Reformat existing results with the new options and units.
         if formatting_changed {
-            self.session.engine.format_options = self.config.format_options();
-            self.refresh_history();
+            self.refresh_history(&previous);
         }
 
         // Theme/font changes are picked up by the per-frame sync in app.rs.
@@ -270,6 +773,33 @@ impl NumbatApp {
 
         self.settings_error = None;
         self.show_settings = false;
-        let _ = ctx;
     }
 }
+
+/// Takes the next key press from the input, so that it reaches no widget.
+/// Copy, cut and paste arrive as clipboard events and are turned back
+/// into their key presses.
+fn take_key_press(ui: &mut egui::Ui) -> Option<(egui::Modifiers, egui::Key)> {
+    ui.input_mut(|i| {
+        let index = i.events.iter().position(|e| {
+            matches!(
+                e,
+                egui::Event::Key { pressed: true, .. }
+                    | egui::Event::Copy
+                    | egui::Event::Cut
+                    | egui::Event::Paste(_)
+            )
+        })?;
+        let command = actions::pressed_modifiers(egui::Modifiers::COMMAND);
+        let press = match i.events.remove(index) {
+            egui::Event::Key { key, modifiers, .. } => (modifiers, key),
+            egui::Event::Copy => (command, egui::Key::C),
+            egui::Event::Cut => (command, egui::Key::X),
+            _ => (command, egui::Key::V),
+        };
+        // Keep the rest of the chord away from the widgets (Escape, Enter, …).
+        i.events
+            .retain(|e| !matches!(e, egui::Event::Key { .. } | egui::Event::Text(_)));
+        Some(press)
+    })
+}
@@ -0,0 +1,197 @@
+//! Entry helpers for the Unicode symbols numbat understands: LaTeX-style
+//! escapes expanded with Tab (`\alpha` → `α`, `^2` → `²`), ASCII ligatures
+//! substituted while typing (`->` → `→`), and the symbol palette.
+
+use egui::{RichText, Stroke};
+use numbat::unicode_input::UNICODE_INPUT;
+
+use crate::theme::Palette;
+
+/// Escapes that expand differently from numbat's table: `\mu` gives the
+/// micro sign, which is how the unit prefix is normally written.
+const ESCAPE_OVERRIDES: &[(&str, &str)] = &[("mu", "µ")];
+
+/// ASCII sequences replaced while typing, when enabled.
+const LIGATURES: &[(&str, &str)] = &[("->", "→"), ("<=", "≤"), (">=", "≥"), ("!=", "≠")];
+
+/// Symbols offered by the palette, by group.
+pub const PALETTE_GROUPS: &[(&str, &[&str])] = &[
+    (
+        "Operators",
+        &["→", "×", "·", "÷", "−", "≤", "≥", "≠", "⁻¹", "²", "³"],
+    ),
+    (
+        "Greek",
+        &[
+            "α", "β", "γ", "δ", "ε", "θ", "λ", "µ", "ν", "π", "ρ", "σ", "τ", "φ", "ω", "Γ", "Δ",
+            "Θ", "Λ", "Π", "Σ", "Φ", "Ψ", "Ω",
+        ],
+    ),
+    ("Units", &["°", "′", "″", "Å", "%", "‰"]),
+    ("Currency", &["$", "€", "£", "¥", "₹", "₩"]),
+];
+
+/// The symbol an escape name (without the backslash) stands for.
+pub fn escape_symbol(name: &str) -> Option<&'static str> {
+    ESCAPE_OVERRIDES
+        .iter()
+        .find(|(escape, _)| *escape == name)
+        .map(|(_, symbol)| *symbol)
+        .or_else(|| {
+            UNICODE_INPUT
+                .iter()
+                .find(|(escapes, _)| escapes.contains(&name))
+                .map(|(_, symbol)| *symbol)
+        })
+}
+
+/// The escape that produces `symbol`, for hints in the palette.
+pub fn escape_name(symbol: &str) -> Option<&'static str> {
+    ESCAPE_OVERRIDES
+        .iter()
+        .find(|(_, s)| *s == symbol)
+        .map(|(escape, _)| *escape)
+        .or_else(|| {
+            UNICODE_INPUT
+                .iter()
+                .find(|(_, s)| *s == symbol)
+                .map(|(escapes, _)| escapes[0])
+        })
+}
+
+fn superscript(c: char) -> Option<char> {
+    Some(match c {
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '-' => '⁻',
+        _ => return None,
+    })
+}
+
+/// The expansion of an escape (`\alpha`) or exponent (`^-2`) that ends
+/// right before the cursor: the byte offset where it starts in `before`,
+/// and the replacement.
+pub fn expand_before_cursor(before: &str) -> Option<(usize, String)> {
+    // `\name`
+    if let Some(start) = before.rfind('\\') {
+        let name = &before[start + 1..];
+        if !name.is_empty() && !name.contains(char::is_whitespace) {
+            if let Some(symbol) = escape_symbol(name) {
+                return Some((start, symbol.to_owned()));
+            }
+        }
+    }
+
+    // `^2`, `^-3`: numbat reads a single superscript digit 1–9 (with an
+    // optional minus), so longer or zero exponents stay as typed.
+    let mut chars = before.char_indices().rev();
+    let digit = chars.next().filter(|(_, c)| matches!(c, '1'..='9'))?.1;
+    let (mut at, mut previous) = chars.next()?;
+    let mut exponent = vec![digit];
+    if previous == '-' {
+        exponent.insert(0, '-');
+        (at, previous) = chars.next()?;
+    }
+    let replacement = exponent.into_iter().filter_map(superscript).collect();
+    (previous == '^').then_some((at, replacement))
+}
+
+/// The substitution for an ASCII ligature completed by typing `typed`
+/// right after `before`: the number of characters to take back from
+/// `before`, and the symbol replacing them together with `typed`.
+pub fn ligature(before: &str, typed: char) -> Option<(usize, &'static str)> {
+    LIGATURES.iter().find_map(|(ascii, symbol)| {
+        let head = ascii.strip_suffix(typed)?;
+        before
+            .ends_with(head)
+            .then(|| (head.chars().count(), *symbol))
+    })
+}
+
+/// The symbol palette: a compact grid of buttons, grouped. Returns the
+/// symbol that was clicked.
+pub fn symbol_palette(
+    ui: &mut egui::Ui,
+    palette: &Palette,
+    font_size: f32,
+) -> Option<&'static str> {
+    let mut clicked = None;
+    for (group, symbols) in PALETTE_GROUPS {
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing = egui::vec2(4.0, 4.0);
+            ui.add_sized(
+                egui::vec2(72.0, font_size + 8.0),
+                egui::Label::new(RichText::new(*group).size(11.0).color(palette.text_faint)),
+            );
+            for symbol in *symbols {
+                let button = egui::Button::new(RichText::new(*symbol).monospace().size(font_size))
+                    .stroke(Stroke::new(1.0, palette.border))
+                    .min_size(egui::vec2(font_size + 12.0, font_size + 8.0));
+                let mut response = ui.add(button);
+                if let Some(escape) = escape_name(symbol) {
+                    response = response.on_hover_text(format!("\\{escape}  then Tab"));
+                }
+                if response.clicked() {
+                    clicked = Some(*symbol);
+                }
+            }
+        });
+    }
+    clicked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_escapes() {
+        assert_eq!(expand_before_cursor("2 \\alpha"), Some((2, "α".to_owned())));
+        assert_eq!(expand_before_cursor("3 \\mu"), Some((2, "µ".to_owned())));
+        assert_eq!(expand_before_cursor("\\to"), Some((0, "→".to_owned())));
+        assert_eq!(expand_before_cursor("\\nonsense"), None);
+    }
+
+    #[test]
+    fn expands_exponents() {
+        assert_eq!(expand_before_cursor("m^2"), Some((1, "²".to_owned())));
+        assert_eq!(expand_before_cursor("s^-1"), Some((1, "⁻¹".to_owned())));
+        // Numbat rejects multi-digit and zero superscript exponents.
+        assert_eq!(expand_before_cursor("s^-12"), None);
+        assert_eq!(expand_before_cursor("m^10"), None);
+        assert_eq!(expand_before_cursor("m^0"), None);
+        assert_eq!(expand_before_cursor("m^-0"), None);
+        assert_eq!(expand_before_cursor("2^x"), None);
+        assert_eq!(expand_before_cursor("12"), None);
+    }
+
+    #[test]
+    fn substitutes_ligatures() {
+        assert_eq!(ligature("5 m -", '>'), Some((1, "→")));
+        assert_eq!(ligature("a <", '='), Some((1, "≤")));
+        assert_eq!(ligature("a =", '='), None);
+    }
+
+    #[test]
+    fn palette_symbols_lex() {
+        // Everything offered must lex as something numbat accepts.
+        for (_, symbols) in PALETTE_GROUPS {
+            for symbol in *symbols {
+                let tokens = crate::syntax::tokenize(symbol);
+                assert!(
+                    tokens
+                        .iter()
+                        .all(|t| t.kind != crate::syntax::TokenKind::Invalid),
+                    "{symbol} does not lex"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! The watch panel above the history: pinned expressions, re-evaluated
+//! through the engine's side-effect-free preview whenever the session's
+//! definitions change, and on an interval for those reading the clock.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use egui::{Frame, Margin, RichText, Stroke};
+use numbat::markup::Markup;
+
+use crate::app::NumbatApp;
+use crate::syntax::{self, Names};
+use crate::theme::markup_job;
+
+/// Functions whose value changes with time alone.
+const CLOCK_FUNCTIONS: &[&str] = &["now", "today"];
+
+/// How long the change indicator stays after a value changed.
+const CHANGE_SECONDS: f64 = 5.0;
+
+#[derive(Default)]
+pub struct WatchPanel {
+    /// Text of the watch being added.
+    pub draft: String,
+    values: HashMap<String, WatchValue>,
+    /// The names the values were evaluated with, and when the clock
+    /// watches were last evaluated.
+    evaluated_for: Option<(Arc<Names>, f64)>,
+}
+
+struct WatchValue {
+    /// `None` while the expression has no value (an error).
+    current: Option<(Markup, String)>,
+    /// The previous value and when it changed.
+    change: Option<(Option<String>, f64)>,
+}
+
+impl NumbatApp {
+    pub fn watch_panel(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        let font_size = self.config.ui.font_size;
+        self.refresh_watches(ui.ctx());
+
+        egui::Panel::top("watch_panel")
+            .frame(
+                Frame::new()
+                    .fill(palette.bg_raised)
+                    .stroke(Stroke::new(1.0, palette.border))
+                    .inner_margin(Margin::symmetric(18, 10)),
+            )
+            .show_separator_line(false)
+            .show_inside(ui, |ui| {
+                let now = ui.input(|i| i.time);
+                let mut copy = None;
+                let mut remove = None;
+                for (index, expression) in self.config.watch.expressions.iter().enumerate() {
+                    let Some(value) = self.watch.values.get(expression) else {
+                        continue;
+                    };
+                    ui.horizontal(|ui| {
+                        ui.push_id(index, |ui| {
+                            ui.label(
+                                RichText::new(expression)
+                                    .monospace()
+                                    .size(font_size - 1.0)
+                                    .color(palette.text_dim),
+                            );
+                            ui.label(RichText::new("=").monospace().color(palette.text_faint));
+                            match &value.current {
+                                Some((markup, plain)) => {
+                                    let label = ui
+                                        .add(
+                                            egui::Label::new(markup_job(
+                                                markup, &palette, font_size,
+                                            ))
+                                            .sense(egui::Sense::click()),
+                                        )
+                                        .on_hover_cursor(egui::CursorIcon::Copy)
+                                        .on_hover_text("Click to copy");
+                                    if label.clicked() {
+                                        copy = Some(plain.clone());
+                                    }
+                                }
+                                None => {
+                                    ui.label(RichText::new("—").color(palette.text_faint))
+                                        .on_hover_text("No value with the current definitions");
+                                }
+                            }
+                            if let Some((previous, at)) = &value.change {
+                                if now - at < CHANGE_SECONDS {
+                                    let current = value.current.as_ref().map(|(_, plain)| plain);
+                                    let arrow = match previous.as_deref().zip(current) {
+                                        Some((old, new)) => match compare_values(old, new) {
+                                            Some(Ordering::Less) => "▲",
+                                            Some(Ordering::Greater) => "▼",
+                                            _ => "●",
+                                        },
+                                        None => "●",
+                                    };
+                                    let was = previous.as_deref().unwrap_or("no value");
+                                    ui.label(RichText::new(arrow).size(11.0).color(palette.value))
+                                        .on_hover_text(format!("Was {was}"));
+                                    ui.ctx().request_repaint_after(
+                                        std::time::Duration::from_secs_f64(CHANGE_SECONDS),
+                                    );
+                                }
+                            }
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    let button = egui::Button::new(
+                                        RichText::new("×").color(palette.text_dim),
+                                    )
+                                    .frame(false);
+                                    if ui.add(button).on_hover_text("Stop watching").clicked() {
+                                        remove = Some(index);
+                                    }
+                                },
+                            );
+                        });
+                    });
+                }
+
+                let add = ui.add(
+                    egui::TextEdit::singleline(&mut self.watch.draft)
+                        .hint_text("Watch an expression…")
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(f32::INFINITY),
+                );
+                if add.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    let expression = std::mem::take(&mut self.watch.draft);
+                    self.add_watch(&expression);
+                }
+
+                if let Some(text) = copy {
+                    self.copy_to_clipboard(ui.ctx(), text);
+                }
+                if let Some(index) = remove {
+                    let mut expressions = self.config.watch.expressions.clone();
+                    expressions.remove(index);
+                    self.set_watches(expressions);
+                }
+            });
+    }
+
+    /// Pins `expression` to the watch panel (and shows the panel).
+    pub fn add_watch(&mut self, expression: &str) {
+        let expression = expression.trim();
+        self.show_watches = true;
+        if expression.is_empty()
+            || self
+                .config
+                .watch
+                .expressions
+                .iter()
+                .any(|e| e == expression)
+        {
+            return;
+        }
+        let mut expressions = self.config.watch.expressions.clone();
+        expressions.push(expression.to_owned());
+        self.set_watches(expressions);
+    }
+
+    fn set_watches(&mut self, expressions: Vec<String>) {
+        self.settings_draft.watch.expressions = expressions.clone();
+        self.config.watch.expressions = expressions;
+        if let Err(e) = self.config.save() {
+            log::warn!("{e}");
+        }
+    }
+
+    /// Evaluates the watches that are new, all of them when the
+    /// definitions changed, and those reading the clock when the refresh
+    /// interval has passed.
+    fn refresh_watches(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        let names = self.session.names();
+        let refresh = f64::from(self.config.watch.refresh_seconds);
+        let (definitions_changed, clock_due) = match &self.watch.evaluated_for {
+            Some((built_for, at)) => (
+                !Arc::ptr_eq(built_for, &names),
+                refresh > 0.0 && now - at >= refresh,
+            ),
+            None => (true, true),
+        };
+
+        let expressions = &self.config.watch.expressions;
+        self.watch
+            .values
+            .retain(|expression, _| expressions.contains(expression));
+        let mut reads_clock = false;
+        for expression in expressions {
+            let clock = uses_clock(expression);
+            reads_clock |= clock;
+            let known = self.watch.values.contains_key(expression);
+            if known && !definitions_changed && !(clock && clock_due) {
+                continue;
+            }
+            let current = self
+                .session
+                .engine
+                .preview(expression)
+                .map(|value| (value.markup, value.plain));
+            match self.watch.values.get_mut(expression) {
+                Some(value) => {
+                    let plain = |v: &Option<(Markup, String)>| v.as_ref().map(|(_, p)| p.clone());
+                    if plain(&value.current) != plain(&current) {
+                        value.change = Some((plain(&value.current), now));
+                    }
+                    value.current = current;
+                }
+                None => {
+                    self.watch.values.insert(
+                        expression.clone(),
+                        WatchValue {
+                            current,
+                            change: None,
+                        },
+                    );
+                }
+            }
+        }
+
+        let at = match &self.watch.evaluated_for {
+            Some((_, at)) if !clock_due => *at,
+            _ => now,
+        };
+        self.watch.evaluated_for = Some((names, at));
+        if reads_clock && refresh > 0.0 {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(refresh));
+        }
+    }
+}
+
+/// Whether `expression` calls a function whose value changes with time.
+fn uses_clock(expression: &str) -> bool {
+    let (_, uses) = syntax::dependencies(expression);
+    uses.iter().any(|name| CLOCK_FUNCTIONS.contains(name))
+}
+
+/// Orders two results by their leading number, when they are in the same
+/// unit (`12.5 m` < `13 m`); `None` when they can't be compared.
+fn compare_values(old: &str, new: &str) -> Option<Ordering> {
+    let split = |value: &str| {
+        let (number, unit) = value.split_once(' ').unwrap_or((value, ""));
+        let number: f64 = number.replace(['_', ','], "").parse().ok()?;
+        Some((number, unit.to_owned()))
+    };
+    let (old, old_unit) = split(old)?;
+    let (new, new_unit) = split(new)?;
+    (old_unit == new_unit)
+        .then(|| old.partial_cmp(&new))
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_values_in_the_same_unit() {
+        assert_eq!(compare_values("12.5 m", "13 m"), Some(Ordering::Less));
+        assert_eq!(
+            compare_values("1_200 kg", "900 kg"),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(compare_values("3", "3"), Some(Ordering::Equal));
+        assert_eq!(compare_values("12 m", "13 ft"), None);
+        assert_eq!(compare_values("2024-05-01", "2024-05-02"), None);
+        assert!(uses_clock(r#"now() -> tz("Asia/Tokyo")"#));
+        assert!(!uses_clock("budget - spent"));
+    }
+}